@@ -13,16 +13,22 @@ if [ -z "$REAL_USER" ]; then
     exit 1
 fi
 
-# Copy systemd service file
-cp github-sync.service /etc/systemd/system/github-sync@.service
+# The folder to sync, as named in .github-sync.json
+FOLDER=${1:-default}
+SCRIPT_DIR=$(cd "$(dirname "$0")" && pwd)
+TEMPLATE="github-sync-${REAL_USER}@.service"
+UNIT="github-sync-${REAL_USER}@$(systemd-escape "$FOLDER").service"
+
+# Install the template unit for this user; the instance names the folder
+sed "s/@USER@/${REAL_USER}/" "${SCRIPT_DIR}/github-sync@.service" > "/etc/systemd/system/${TEMPLATE}"
 
 # Reload systemd daemon
 systemctl daemon-reload
 
-# Enable and start the service for the user
-systemctl enable github-sync@${REAL_USER}
-systemctl start github-sync@${REAL_USER}
+# Enable and start the service for the folder
+systemctl enable "$UNIT"
+systemctl start "$UNIT"
 
-echo "✅ GitHub Sync service installed and enabled for user: ${REAL_USER}"
+echo "✅ GitHub Sync service installed and enabled for user: ${REAL_USER}, folder: ${FOLDER}"
 echo "ℹ️  Service status:"
-systemctl status github-sync@${REAL_USER} 
\ No newline at end of file
+systemctl status "$UNIT"
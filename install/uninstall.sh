@@ -13,14 +13,20 @@ if [ -z "$REAL_USER" ]; then
     exit 1
 fi
 
+FOLDER=${1:-default}
+TEMPLATE="github-sync-${REAL_USER}@.service"
+UNIT="github-sync-${REAL_USER}@$(systemd-escape "$FOLDER").service"
+
 # Stop and disable the service
-systemctl stop github-sync@${REAL_USER}
-systemctl disable github-sync@${REAL_USER}
+systemctl stop "$UNIT"
+systemctl disable "$UNIT"
 
-# Remove service file
-rm -f /etc/systemd/system/github-sync@.service
+# Remove the template once no other folder of this user is still enabled
+if ! compgen -G "/etc/systemd/system/*.wants/github-sync-${REAL_USER}@*.service" > /dev/null; then
+    rm -f "/etc/systemd/system/${TEMPLATE}"
+fi
 
 # Reload systemd daemon
 systemctl daemon-reload
 
-echo "✅ GitHub Sync service uninstalled for user: ${REAL_USER}" 
\ No newline at end of file
+echo "✅ GitHub Sync service uninstalled for user: ${REAL_USER}, folder: ${FOLDER}" 
\ No newline at end of file
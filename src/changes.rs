@@ -0,0 +1,193 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use notify::{Event, EventKind};
+use notify::event::{ModifyKind, RenameMode};
+
+/// A single change to the watched tree, derived from a notify event
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+impl Change {
+    /// Turn a notify event into changes, looking at every path it carries.
+    ///
+    /// `in_tree` decides whether a path belongs to the synced tree. A rename whose
+    /// source is outside the tree becomes a creation, one whose target is outside
+    /// becomes a removal.
+    pub fn from_event(event: &Event, in_tree: impl Fn(&Path) -> bool) -> Vec<Change> {
+        let tracked = |paths: &[PathBuf]| -> Vec<PathBuf> {
+            paths.iter().filter(|p| in_tree(p)).cloned().collect()
+        };
+
+        match event.kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                let (from, to) = (&event.paths[0], &event.paths[1]);
+                match (in_tree(from), in_tree(to)) {
+                    (true, true) => vec![Change::Renamed { from: from.clone(), to: to.clone() }],
+                    (true, false) => vec![Change::Removed(from.clone())],
+                    (false, true) => vec![Change::Created(to.clone())],
+                    (false, false) => Vec::new(),
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                tracked(&event.paths).into_iter().map(Change::Removed).collect()
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                tracked(&event.paths).into_iter().map(Change::Created).collect()
+            }
+            EventKind::Create(_) => {
+                tracked(&event.paths).into_iter().map(Change::Created).collect()
+            }
+            EventKind::Modify(_) => {
+                tracked(&event.paths).into_iter().map(Change::Modified).collect()
+            }
+            EventKind::Remove(_) => {
+                tracked(&event.paths).into_iter().map(Change::Removed).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Change::Created(p) => format!("Created: {}", p.display()),
+            Change::Modified(p) => format!("Modified: {}", p.display()),
+            Change::Removed(p) => format!("Removed: {}", p.display()),
+            Change::Renamed { from, to } => format!("Renamed: {} -> {}", from.display(), to.display()),
+        }
+    }
+}
+
+/// The set of paths touched since the last commit, plus the renames among them
+#[derive(Debug, Default, Clone)]
+pub struct ChangeSet {
+    paths: BTreeSet<PathBuf>,
+    renames: Vec<(PathBuf, PathBuf)>,
+}
+
+impl ChangeSet {
+    pub fn record(&mut self, change: Change) {
+        match change {
+            Change::Created(p) | Change::Modified(p) | Change::Removed(p) => {
+                self.paths.insert(p);
+            }
+            Change::Renamed { from, to } => {
+                self.paths.insert(from.clone());
+                self.paths.insert(to.clone());
+
+                // a -> b followed by b -> c is a single a -> c rename
+                if let Some(rename) = self.renames.iter_mut().find(|(_, t)| *t == from) {
+                    rename.1 = to;
+                } else {
+                    self.renames.push((from, to));
+                }
+                self.renames.retain(|(f, t)| f != t);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.contains(path)
+    }
+
+    /// Every path touched, including both sides of each rename
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.paths.iter()
+    }
+
+    pub fn renames(&self) -> &[(PathBuf, PathBuf)] {
+        &self.renames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, RemoveKind};
+
+    fn root() -> PathBuf {
+        PathBuf::from("/sync")
+    }
+
+    fn in_root(path: &Path) -> bool {
+        path.starts_with(root())
+    }
+
+    fn rename(from: &str, to: &str) -> Event {
+        Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+            .add_path(PathBuf::from(from))
+            .add_path(PathBuf::from(to))
+    }
+
+    #[test]
+    fn test_rename_within_tree() {
+        let changes = Change::from_event(&rename("/sync/a.txt", "/sync/b.txt"), in_root);
+        assert_eq!(changes, vec![Change::Renamed {
+            from: root().join("a.txt"),
+            to: root().join("b.txt"),
+        }]);
+    }
+
+    #[test]
+    fn test_rename_into_tree() {
+        let changes = Change::from_event(&rename("/tmp/a.txt", "/sync/a.txt"), in_root);
+        assert_eq!(changes, vec![Change::Created(root().join("a.txt"))]);
+
+        let to_only = Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::To)))
+            .add_path(root().join("a.txt"));
+        assert_eq!(Change::from_event(&to_only, in_root), vec![Change::Created(root().join("a.txt"))]);
+    }
+
+    #[test]
+    fn test_rename_out_of_tree() {
+        let changes = Change::from_event(&rename("/sync/a.txt", "/tmp/a.txt"), in_root);
+        assert_eq!(changes, vec![Change::Removed(root().join("a.txt"))]);
+
+        let from_only = Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::From)))
+            .add_path(root().join("a.txt"));
+        assert_eq!(Change::from_event(&from_only, in_root), vec![Change::Removed(root().join("a.txt"))]);
+    }
+
+    #[test]
+    fn test_all_paths_are_considered() {
+        let event = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(PathBuf::from("/sync/.git/index"))
+            .add_path(root().join("a.txt"))
+            .add_path(root().join("b.txt"));
+        let in_tree = |p: &Path| in_root(p) && !p.components().any(|c| c.as_os_str() == ".git");
+
+        assert_eq!(Change::from_event(&event, in_tree), vec![
+            Change::Created(root().join("a.txt")),
+            Change::Created(root().join("b.txt")),
+        ]);
+
+        let removed = Event::new(EventKind::Remove(RemoveKind::Any)).add_path(PathBuf::from("/tmp/x"));
+        assert!(Change::from_event(&removed, in_root).is_empty());
+    }
+
+    #[test]
+    fn test_change_set_collapses_rename_chains() {
+        let mut set = ChangeSet::default();
+        set.record(Change::Renamed { from: root().join("a"), to: root().join("b") });
+        set.record(Change::Renamed { from: root().join("b"), to: root().join("c") });
+        set.record(Change::Modified(root().join("d")));
+
+        assert_eq!(set.renames(), &[(root().join("a"), root().join("c"))]);
+        assert_eq!(set.len(), 4);
+
+        set.record(Change::Renamed { from: root().join("c"), to: root().join("a") });
+        assert!(set.renames().is_empty());
+    }
+}
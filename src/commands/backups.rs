@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{DateTime, Local};
+use colored::*;
+use crate::config::Config;
+use crate::error::{Result, GitHubSyncError};
+use crate::git::{short_id, Backup, GitSync};
+use crate::ipc::{self, Request};
+use crate::lock::FileLock;
+use crate::logger;
+use dirs;
+
+pub fn list(remote: bool, json: bool) -> Result<()> {
+    let Some(git) = open()? else {
+        return Ok(());
+    };
+    if remote {
+        // Fetching rewrites refs, so it goes through whichever process holds the repository
+        match ipc::request(&Request::FetchBackups)? {
+            Some(response) => {
+                response.into_result()?;
+            }
+            None => {
+                let _lock = FileLock::folder_or_busy(&sync_root())?;
+                git.fetch_backups()?;
+            }
+        }
+    }
+    let backups = git.backups()?;
+
+    if json {
+        let out = serde_json::to_string_pretty(&backups)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Failed to serialize backups: {}", e)))?;
+        println!("{}", out);
+        return Ok(());
+    }
+
+    if backups.is_empty() {
+        logger::info("No backups. Pulls make one when they replace diverged local history");
+        return Ok(());
+    }
+
+    println!("\n💾 Backups of replaced local history:\n");
+    for backup in &backups {
+        print_backup(backup);
+    }
+    println!("\nSee what one holds with 'ghs backups show <name>'\n");
+    Ok(())
+}
+
+pub fn show(name: &str, json: bool) -> Result<()> {
+    let Some(git) = open()? else {
+        return Ok(());
+    };
+    let details = git.backup_details(name)?;
+
+    if json {
+        let out = serde_json::to_string_pretty(&details)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Failed to serialize backup: {}", e)))?;
+        println!("{}", out);
+        return Ok(());
+    }
+
+    println!();
+    print_backup(&details.backup);
+    if !details.commits.is_empty() {
+        println!("\nCommits not in the current history:\n");
+        for entry in &details.commits {
+            let time: DateTime<Local> = entry.time.into();
+            println!(
+                "{} {} {}: {}",
+                short_id(&entry.id).yellow(),
+                time.format("%Y-%m-%d %H:%M:%S"),
+                entry.device.green(),
+                entry.summary
+            );
+        }
+    }
+    if details.files.is_empty() {
+        println!("\nNo files differ from the current history\n");
+        return Ok(());
+    }
+    println!("\nFiles that differ from the current history:\n");
+    for file in &details.files {
+        let marker = match file.change {
+            "added" => "A".green(),
+            "deleted" => "D".red(),
+            "renamed" => "R".cyan(),
+            _ => "M".yellow(),
+        };
+        println!("    {} {}", marker, file.path.display());
+    }
+    println!("\nRestore them with 'ghs backups restore {} [path]'\n", name);
+    Ok(())
+}
+
+pub fn restore(name: &str, path: Option<PathBuf>) -> Result<()> {
+    let Some(git) = open()? else {
+        return Ok(());
+    };
+    // The files land in the sync folder, so a running daemon commits them as a new change
+    let restored = git.restore_backup(name, path.as_deref())?;
+    match path {
+        Some(path) => logger::success(&format!("Restored {} ({} files) from backup {}", path.display(), restored, name)),
+        None => logger::success(&format!("Restored {} files from backup {}", restored, name)),
+    }
+    Ok(())
+}
+
+pub fn prune(older_than: Option<Duration>) -> Result<()> {
+    let request = Request::PruneBackups { older_than_secs: older_than.map(|age| age.as_secs()) };
+    if let Some(response) = ipc::request(&request)? {
+        logger::success(&response.into_result()?);
+        return Ok(());
+    }
+
+    let Some(git) = open()? else {
+        return Ok(());
+    };
+    let _lock = FileLock::folder_or_busy(&sync_root())?;
+    let pruned = match older_than {
+        Some(age) => git.delete_backups(Some(age), 0)?,
+        None => git.prune_backups()?,
+    };
+    for name in &pruned {
+        logger::info(&format!("Deleted backup {}", name));
+    }
+    logger::success(&format!("Deleted {} backups", pruned.len()));
+    Ok(())
+}
+
+fn print_backup(backup: &Backup) {
+    let time: DateTime<Local> = backup.time.into();
+    println!(
+        "{} {} {} ({} commits not in the current history)",
+        backup.name.bold(),
+        short_id(&backup.commit).yellow(),
+        time.format("%Y-%m-%d %H:%M:%S"),
+        backup.unique_commits
+    );
+}
+
+fn sync_root() -> PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".github-sync")
+}
+
+/// Reading backups and restoring files from them never needs the daemon or the
+/// lock; fetching and pruning rewrite refs and take one or the other
+fn open() -> Result<Option<GitSync>> {
+    let sync_root = sync_root();
+    if !sync_root.join(".git").exists() {
+        logger::error("No sync history yet. Use 'ghs start' first.");
+        return Ok(None);
+    }
+
+    let config = Config::load()?;
+    let git = GitSync::open(&sync_root, &config, &config.repository_settings())?;
+    Ok(Some(git))
+}
@@ -1,5 +1,3 @@
-use std::io::{self, Write};
-use colored::*;
 use crate::config::Config;
 use crate::error::Result;
 use crate::logger;
@@ -17,6 +15,12 @@ pub fn handle(
         branch: "main".to_string(),
         sync_paths: Vec::new(),
         sync_interval: 300, // 5 minutes default
+        folders: Default::default(),
+        log_level: None,
+        trash: Default::default(),
+        history: Default::default(),
+        backups: Default::default(),
+        secrets: Default::default(),
     });
 
     // Update config with provided values
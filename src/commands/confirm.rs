@@ -0,0 +1,35 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::git::GitSync;
+use crate::ipc::{self, Request};
+use crate::lock::FileLock;
+use crate::logger;
+use dirs;
+
+/// Allow a mass deletion to be pushed: the daemon's held one, or without a daemon
+/// the unpushed commits in the repository, which are pushed right away
+pub fn handle(folder: Option<String>) -> Result<()> {
+    if let Some(response) = ipc::request(&Request::Confirm { folder })? {
+        logger::success(&response.into_result()?);
+        return Ok(());
+    }
+
+    let config = Config::load()?;
+    let sync_root = dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".github-sync");
+
+    // The daemon may be starting up and not answering yet; never share the repository with it
+    let _lock = FileLock::folder_or_busy(&sync_root)?;
+    let git = GitSync::open(&sync_root, &config, &config.repository_settings())?;
+
+    let Some(deletion) = git.mass_deletion()? else {
+        logger::info("No deletions are waiting for confirmation");
+        return Ok(());
+    };
+    git.confirm_push()?;
+    logger::sync(&format!("Confirmed deleting {} of {} files, pushing...", deletion.deleted, deletion.tracked));
+    git.sync()?;
+    logger::success("Deletions confirmed and pushed");
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use crate::config::Config;
+use crate::crypto::{self, Key};
+use crate::error::{Result, GitHubSyncError};
+use crate::git::GitSync;
+use crate::ipc::{self, Request};
+use crate::lock::FileLock;
+use crate::logger;
+use dirs;
+
+pub fn init(names: bool) -> Result<()> {
+    let (_lock, mut git) = open()?;
+    if git.is_encrypted() {
+        logger::info("Encryption is already set up; 'ghs encrypt export' shows the key");
+        return Ok(());
+    }
+
+    let passphrase = crypto::prompt_passphrase("Passphrase: ")?;
+    if passphrase.chars().count() < 8 {
+        return Err(GitHubSyncError::EncryptionError("Use a passphrase of at least 8 characters".to_string()));
+    }
+    if crypto::prompt_passphrase("Repeat passphrase: ")? != passphrase {
+        return Err(GitHubSyncError::EncryptionError("The passphrases do not match".to_string()));
+    }
+    let key = Key::from_passphrase(&passphrase, names)?;
+
+    logger::sync("Encrypting the sync history and replacing it on the remote...");
+    git.enable_encryption(key.clone())?;
+    logger::success("Encryption enabled; everything pushed from now on is encrypted");
+    logger::warn("GitHub may keep the earlier unencrypted commits reachable by id; rotate any secrets they held");
+    println!(
+        "\nSet up your other devices with 'ghs encrypt import' and the passphrase, or this key:\n\n    {}\n",
+        key.export()
+    );
+    Ok(())
+}
+
+pub fn export() -> Result<()> {
+    let config = Config::load()?;
+    let git = GitSync::new(sync_root(), &config.remote_url, &config.branch)?;
+    match git.encryption_key() {
+        Some(key) => println!("{}", key.export()),
+        None => logger::error("Encryption is not set up. Use 'ghs encrypt init' first."),
+    }
+    Ok(())
+}
+
+/// Set up this device with the key from 'ghs encrypt export', or with the
+/// passphrase the key was made from
+pub fn import(key: Option<String>) -> Result<()> {
+    let input = match key {
+        Some(key) => key,
+        None => crypto::prompt_passphrase("Key from 'ghs encrypt export', or the passphrase: ")?,
+    };
+
+    let (_lock, mut git) = open()?;
+    if git.is_encrypted() {
+        logger::info("This device already has an encryption key");
+        return Ok(());
+    }
+    let key = if Key::is_exported(&input) {
+        Key::import(&input)?
+    } else {
+        logger::sync("Deriving the key from the passphrase...");
+        git.key_from_passphrase(&input)?
+    };
+    git.import_key(key)?;
+    logger::success("Key imported; the encrypted history is synced to this device");
+    Ok(())
+}
+
+fn sync_root() -> std::path::PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".github-sync")
+}
+
+/// Open the repository for changing its key. The daemon keeps the key it started
+/// with, so it has to be stopped rather than asked.
+fn open() -> Result<(FileLock, GitSync)> {
+    if ipc::request(&Request::Status)?.is_some() {
+        return Err(GitHubSyncError::EncryptionError(
+            "Stop GitHub Sync with 'ghs stop' before changing encryption".to_string(),
+        ));
+    }
+
+    let config = Config::load()?;
+    let sync_root = sync_root();
+    let lock = FileLock::folder_or_busy(&sync_root)?;
+    let git = GitSync::open(&sync_root, &config, &config.repository_settings())?;
+    Ok((lock, git))
+}
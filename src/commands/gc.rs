@@ -0,0 +1,39 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::git::{Compaction, GitSync};
+use crate::ipc::{self, Request};
+use crate::lock::FileLock;
+use crate::logger;
+use dirs;
+
+pub fn handle(dry_run: bool) -> Result<()> {
+    // The daemon owns the repository while it runs
+    if let Some(response) = ipc::request(&Request::Gc { dry_run })? {
+        logger::success(&response.into_result()?);
+        return Ok(());
+    }
+
+    let config = Config::load()?;
+    let sync_root = dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".github-sync");
+
+    // The daemon may be starting up and not answering yet; never share the repository with it
+    let _lock = FileLock::folder_or_busy(&sync_root)?;
+    let git = GitSync::open(&sync_root, &config, &config.repository_settings())?;
+
+    logger::sync("Compacting history...");
+    let compaction = git.compact(&config.history, dry_run)?;
+    logger::success(&describe(compaction.as_ref(), dry_run));
+    Ok(())
+}
+
+/// One line on what a compaction did or, for a dry run, would do
+pub fn describe(compaction: Option<&Compaction>, dry_run: bool) -> String {
+    match compaction {
+        None => "Nothing old enough to compact".to_string(),
+        Some(c) if dry_run => format!("Would compact {} commits into {}", c.commits_before, c.commits_after),
+        Some(c) if c.pruned => format!("Compacted {} commits into {} and pruned old objects", c.commits_before, c.commits_after),
+        Some(c) => format!("Compacted {} commits into {}", c.commits_before, c.commits_after),
+    }
+}
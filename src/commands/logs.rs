@@ -1,33 +1,152 @@
-use std::process::Command;
-use crate::error::Result;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use colored::*;
+use crate::util::parse_duration;
+use crate::config::Config;
+use crate::error::{Result, GitHubSyncError};
+use crate::git::{short_id, GitSync, HistoryEntry, HistoryFilter};
 use crate::logger;
 use dirs;
 
-pub fn handle() -> Result<()> {
+pub struct Options {
+    pub count: usize,
+    pub since: Option<SystemTime>,
+    pub until: Option<SystemTime>,
+    pub path: Option<PathBuf>,
+    pub device: Option<String>,
+    pub folder: Option<String>,
+    pub json: bool,
+}
+
+pub fn handle(options: Options) -> Result<()> {
     // Get sync root
     let sync_root = dirs::home_dir()
         .expect("Could not find home directory")
         .join(".github-sync");
 
-    // Get git log
-    let output = Command::new("git")
-        .args([
-            "log",
-            "--pretty=format:%C(yellow)%h %C(reset)%ad %C(green)%an%C(reset): %s",
-            "--date=relative",
-            "-n",
-            "20"
-        ])
-        .current_dir(&sync_root)
-        .output()?;
-
-    if output.status.success() {
-        println!("\n📋 Last 20 sync operations:\n");
-        println!("{}", String::from_utf8_lossy(&output.stdout));
-    } else {
-        logger::error("Failed to get sync history");
-        println!("{}", String::from_utf8_lossy(&output.stderr));
+    if !sync_root.join(".git").exists() {
+        logger::error("No sync history yet. Use 'ghs start' first.");
+        return Ok(());
     }
 
+    let config = Config::load()?;
+    let git = GitSync::new(&sync_root, &config.remote_url, &config.branch)?;
+    let filter = HistoryFilter {
+        limit: Some(options.count),
+        since: options.since,
+        until: options.until,
+        paths: filter_path(&sync_root, options.folder.as_deref(), options.path.as_deref())
+            .into_iter()
+            .collect(),
+        device: options.device,
+    };
+    let history = git.history(&filter)?;
+
+    if options.json {
+        let out = serde_json::to_string_pretty(&history)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Failed to serialize history: {}", e)))?;
+        println!("{}", out);
+    } else {
+        print_text(&history);
+    }
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// The path to filter on, relative to the sync root. A relative `--path` is taken
+/// within `--folder` when one is given, otherwise within the sync root.
+fn filter_path(sync_root: &Path, folder: Option<&str>, path: Option<&Path>) -> Option<PathBuf> {
+    let base = folder.map(PathBuf::from).unwrap_or_default();
+    match path {
+        Some(path) if path.is_absolute() => Some(path.strip_prefix(sync_root).unwrap_or(path).to_path_buf()),
+        Some(path) => Some(base.join(path)),
+        None if folder.is_some() => Some(base),
+        None => None,
+    }
+}
+
+/// Parse `--since`/`--until`: a date (`2026-10-01`), an RFC 3339 timestamp, or a
+/// duration ago such as `2h` or `7d`.
+pub fn parse_time(input: &str) -> std::result::Result<SystemTime, String> {
+    let input = input.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Ok(time.into());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        return Local.from_local_datetime(&midnight)
+            .earliest()
+            .map(SystemTime::from)
+            .ok_or_else(|| format!("'{}' does not exist in the local time zone", input));
+    }
+    match parse_duration(input) {
+        Ok(ago) => Ok(SystemTime::now() - ago),
+        Err(_) => Err(format!("'{}' is not a date, an RFC 3339 time or a duration like 2h", input)),
+    }
+}
+
+fn print_text(history: &[HistoryEntry]) {
+    if history.is_empty() {
+        logger::info("No matching sync operations");
+        return;
+    }
+
+    println!("\n📋 Last {} sync operations:\n", history.len());
+    for entry in history {
+        let time: DateTime<Local> = entry.time.into();
+        println!(
+            "{} {} {}: {}",
+            short_id(&entry.id).yellow(),
+            time.format("%Y-%m-%d %H:%M:%S"),
+            entry.device.green(),
+            entry.summary
+        );
+        for file in &entry.files {
+            match &file.from {
+                Some(from) => println!("    {} {} -> {}", "R".cyan(), from.display(), file.path.display()),
+                None => {
+                    let marker = match file.change {
+                        "added" => "A".green(),
+                        "deleted" => "D".red(),
+                        _ => "M".yellow(),
+                    };
+                    println!("    {} {}", marker, file.path.display());
+                }
+            }
+        }
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_filter_path() {
+        let root = Path::new("/home/me/.github-sync");
+        assert_eq!(filter_path(root, None, None), None);
+        assert_eq!(filter_path(root, Some("notes"), None), Some(PathBuf::from("notes")));
+        assert_eq!(filter_path(root, Some("notes"), Some(Path::new("a.txt"))), Some(PathBuf::from("notes/a.txt")));
+        assert_eq!(filter_path(root, None, Some(Path::new("notes/a.txt"))), Some(PathBuf::from("notes/a.txt")));
+        assert_eq!(
+            filter_path(root, Some("photos"), Some(Path::new("/home/me/.github-sync/notes/a.txt"))),
+            Some(PathBuf::from("notes/a.txt"))
+        );
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let rfc = parse_time("2026-10-19T14:03:22+00:00").unwrap();
+        assert_eq!(rfc.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs(), 1_792_418_602);
+
+        let date: DateTime<Local> = parse_time("2026-10-01").unwrap().into();
+        assert_eq!(date.format("%Y-%m-%d %H:%M").to_string(), "2026-10-01 00:00");
+
+        let ago = SystemTime::now().duration_since(parse_time("2h").unwrap()).unwrap();
+        assert!(ago > Duration::from_secs(7_140) && ago < Duration::from_secs(7_260));
+
+        assert!(parse_time("yesterday").is_err());
+    }
+}
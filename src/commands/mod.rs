@@ -4,4 +4,13 @@ pub mod status;
 pub mod push;
 pub mod pull;
 pub mod logs;
-pub mod config;
\ No newline at end of file
+pub mod restore;
+pub mod snapshot;
+pub mod backups;
+pub mod encrypt;
+pub mod gc;
+pub mod config;
+pub mod pause;
+pub mod confirm;
+pub mod service;
+pub mod trash;
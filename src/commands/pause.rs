@@ -0,0 +1,26 @@
+use std::time::Duration;
+use crate::error::{Result, GitHubSyncError};
+use crate::ipc::{self, Request};
+use crate::logger;
+
+pub fn pause(folder: Option<String>, duration: Option<Duration>) -> Result<()> {
+    let request = Request::Pause {
+        folder,
+        duration_secs: duration.map(|d| d.as_secs()),
+    };
+    send(request)
+}
+
+pub fn resume(folder: Option<String>) -> Result<()> {
+    send(Request::Resume { folder })
+}
+
+fn send(request: Request) -> Result<()> {
+    match ipc::request(&request)? {
+        Some(response) => {
+            logger::success(&response.into_result()?);
+            Ok(())
+        }
+        None => Err(GitHubSyncError::DaemonError("GitHub Sync is not running".to_string())),
+    }
+}
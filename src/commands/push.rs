@@ -1,10 +1,19 @@
 use crate::error::Result;
 use crate::git::GitSync;
 use crate::config::Config;
+use crate::ipc::{self, Request};
+use crate::lock::FileLock;
 use crate::logger;
 use dirs;
 
 pub fn handle() -> Result<()> {
+    // Let the running daemon do it rather than opening the repository a second time
+    if let Some(response) = ipc::request(&Request::SyncNow)? {
+        // The daemon always pulls and pushes together
+        logger::success(&response.into_result()?);
+        return Ok(());
+    }
+
     // Load config
     let config = Config::load()?;
     
@@ -13,12 +22,11 @@ pub fn handle() -> Result<()> {
         .expect("Could not find home directory")
         .join(".github-sync");
 
+    // The daemon may be starting up and not answering yet; never share the repository with it
+    let _lock = FileLock::folder_or_busy(&sync_root)?;
+
     // Initialize Git sync
-    let git = GitSync::new(
-        &sync_root,
-        &config.remote_url,
-        &config.branch
-    )?;
+    let git = GitSync::open(&sync_root, &config, &config.repository_settings())?;
 
     // Push changes
     logger::sync("Pushing changes to remote...");
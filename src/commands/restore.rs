@@ -0,0 +1,127 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use chrono::{DateTime, Local};
+use colored::*;
+use crate::config::Config;
+use crate::error::{Result, GitHubSyncError};
+use crate::git::{short_id, GitSync, HistoryFilter, Version};
+use crate::logger;
+use dirs;
+
+pub fn restore(path: PathBuf, at: Option<SystemTime>, commit: Option<String>, output: Option<PathBuf>) -> Result<()> {
+    let (sync_root, git) = open()?;
+    let relative = sync_relative(&sync_root, &std::env::current_dir()?, &path)?;
+    let version = match (at, commit) {
+        (_, Some(commit)) => Version::Commit(commit),
+        (Some(at), None) => Version::At(at),
+        (None, None) => Version::Latest,
+    };
+    let dest = output.unwrap_or_else(|| sync_root.join(&relative));
+
+    let restored = git.restore(&relative, &version, &dest)?;
+    let time: DateTime<Local> = restored.time.into();
+    logger::success(&format!(
+        "Restored {} ({} files) from {} ({}, {})",
+        relative.display(),
+        restored.files,
+        short_id(&restored.commit),
+        time.format("%Y-%m-%d %H:%M:%S"),
+        restored.device
+    ));
+    if dest != sync_root.join(&relative) {
+        logger::info(&format!("Written to {}", dest.display()));
+    }
+    Ok(())
+}
+
+pub fn history(path: PathBuf) -> Result<()> {
+    let (sync_root, git) = open()?;
+    let relative = sync_relative(&sync_root, &std::env::current_dir()?, &path)?;
+    let versions = git.history(&HistoryFilter { paths: vec![relative.clone()], ..Default::default() })?;
+
+    if versions.is_empty() {
+        logger::info(&format!("{} is not in the sync history", relative.display()));
+        return Ok(());
+    }
+
+    println!("\n🕘 Versions of {}:\n", relative.display());
+    for entry in &versions {
+        let time: DateTime<Local> = entry.time.into();
+        let changes: Vec<&str> = entry.files.iter().map(|f| f.change).collect();
+        println!(
+            "{} {} {} ({})",
+            short_id(&entry.id).yellow(),
+            time.format("%Y-%m-%d %H:%M:%S"),
+            entry.device.green(),
+            changes.join(", ")
+        );
+    }
+    println!("\nRestore one with 'ghs restore {} --commit <id>'\n", path.display());
+    Ok(())
+}
+
+fn open() -> Result<(PathBuf, GitSync)> {
+    let sync_root = dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".github-sync");
+    if !sync_root.join(".git").exists() {
+        return Err(GitHubSyncError::RestoreError("No sync history yet. Use 'ghs start' first.".to_string()));
+    }
+
+    let config = Config::load()?;
+    let git = GitSync::new(&sync_root, &config.remote_url, &config.branch)?;
+    Ok((sync_root, git))
+}
+
+/// `path` relative to the sync root. Relative paths are taken from the current
+/// directory when it is inside the sync root, otherwise from the sync root itself,
+/// so `ghs restore notes/a.txt` works from anywhere. Deleted paths cannot be
+/// canonicalized, so this is purely lexical.
+fn sync_relative(sync_root: &Path, cwd: &Path, path: &Path) -> Result<PathBuf> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else if cwd.starts_with(sync_root) {
+        cwd.join(path)
+    } else {
+        sync_root.join(path)
+    };
+
+    let mut relative = PathBuf::new();
+    for component in absolute.strip_prefix(sync_root).map_err(|_| {
+        GitHubSyncError::RestoreError(format!("{} is outside {}", path.display(), sync_root.display()))
+    })?.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                if !relative.pop() {
+                    return Err(GitHubSyncError::RestoreError(format!("{} is outside {}", path.display(), sync_root.display())));
+                }
+            }
+            other => relative.push(other),
+        }
+    }
+    Ok(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_relative() {
+        let root = Path::new("/home/me/.github-sync");
+        let home = Path::new("/home/me");
+        let notes = Path::new("/home/me/.github-sync/notes");
+
+        assert_eq!(sync_relative(root, home, Path::new("notes/a.txt")).unwrap(), PathBuf::from("notes/a.txt"));
+        assert_eq!(sync_relative(root, notes, Path::new("a.txt")).unwrap(), PathBuf::from("notes/a.txt"));
+        assert_eq!(sync_relative(root, notes, Path::new("./drafts/../a.txt")).unwrap(), PathBuf::from("notes/a.txt"));
+        assert_eq!(
+            sync_relative(root, home, Path::new("/home/me/.github-sync/notes/a.txt")).unwrap(),
+            PathBuf::from("notes/a.txt")
+        );
+
+        assert!(sync_relative(root, home, Path::new("/etc/passwd")).is_err());
+        assert!(sync_relative(root, notes, Path::new("../../secret")).is_err());
+    }
+}
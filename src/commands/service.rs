@@ -0,0 +1,240 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::config::{Config, FolderSettings};
+use crate::error::{Result, GitHubSyncError};
+use crate::logger;
+
+/// Units are named `github-sync@<folder>.service`, one per synced folder
+const UNIT_PREFIX: &str = "github-sync@";
+/// Same as `ghs start --daemon`'s wait for the initial sync
+const START_TIMEOUT_SECS: u64 = 600;
+/// Restart the daemon when its main loop has not pinged for this long. Syncs and
+/// requested operations run on the worker without holding up the loop; what it
+/// still awaits is local commits and rescans, which can queue behind a remote
+/// check still waiting on a TCP connect.
+const WATCHDOG_SECS: u64 = 180;
+
+/// Write a systemd user unit running `ghs start` for `folder`, then enable and start it.
+pub fn install(user: bool, folder: &str) -> Result<()> {
+    ensure_systemd()?;
+    if !user {
+        return Err(GitHubSyncError::ConfigError(
+            "Only user services are supported; run 'ghs service install --user'".to_string(),
+        ));
+    }
+
+    // The daemon reads its configuration and writes its PID file relative to the
+    // working directory, so the unit runs where the install was done
+    let working_dir = std::env::current_dir()?;
+    let settings = match Config::load() {
+        Ok(config) => config.folder_settings(folder),
+        Err(_) => {
+            logger::warn(&format!(
+                "No configuration found in {}; the service will use the defaults",
+                working_dir.display()
+            ));
+            FolderSettings::default()
+        }
+    };
+
+    let unit = render_unit(&std::env::current_exe()?, folder, &working_dir, &settings);
+    let name = unit_name(folder);
+    let path = unit_path(&name)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, unit)?;
+    logger::info(&format!("Wrote {}", path.display()));
+
+    systemctl(&["daemon-reload"])?;
+    systemctl(&["enable", "--now", &name])?;
+    logger::success(&format!("GitHub Sync service installed and started for '{}'", folder));
+    logger::info("Run 'loginctl enable-linger' to keep it running while you are logged out.");
+    Ok(())
+}
+
+/// Stop and disable the user unit for `folder` and remove its file.
+pub fn uninstall(folder: &str) -> Result<()> {
+    ensure_systemd()?;
+    let name = unit_name(folder);
+    let path = unit_path(&name)?;
+    if !path.exists() {
+        logger::info(&format!("GitHub Sync service for '{}' is not installed.", folder));
+        return Ok(());
+    }
+
+    // Still remove the file if the unit was never loaded
+    if let Err(e) = systemctl(&["disable", "--now", &name]) {
+        logger::warn(&format!("Failed to disable the service: {}", e));
+    }
+    fs::remove_file(&path)?;
+    systemctl(&["daemon-reload"])?;
+    logger::success(&format!("GitHub Sync service for '{}' uninstalled.", folder));
+    Ok(())
+}
+
+/// Show the unit for `folder`, or every installed one.
+pub fn status(folder: Option<&str>) -> Result<()> {
+    ensure_systemd()?;
+    let names = match folder {
+        Some(folder) => vec![unit_name(folder)],
+        None => installed_units()?,
+    };
+    let installed: Vec<String> = names.into_iter().filter(|name| unit_path(name).is_ok_and(|p| p.exists())).collect();
+    if installed.is_empty() {
+        logger::info("GitHub Sync service is not installed. Use 'ghs service install --user -n <name>'.");
+        return Ok(());
+    }
+
+    for name in &installed {
+        logger::info(&format!("Unit file: {}", unit_path(name)?.display()));
+    }
+    // Non-zero just means a unit is not active; systemctl has already said so
+    Command::new("systemctl")
+        .args(["--user", "status", "--no-pager"])
+        .args(&installed)
+        .status()?;
+    Ok(())
+}
+
+fn ensure_systemd() -> Result<()> {
+    if cfg!(target_os = "linux") {
+        Ok(())
+    } else {
+        Err(GitHubSyncError::ConfigError("systemd services are only supported on Linux".to_string()))
+    }
+}
+
+fn unit_dir() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| GitHubSyncError::ConfigError("Could not find config directory".to_string()))?;
+    Ok(config_dir.join("systemd").join("user"))
+}
+
+fn unit_path(name: &str) -> Result<PathBuf> {
+    Ok(unit_dir()?.join(name))
+}
+
+/// Names of the GitHub Sync units in the user unit directory
+fn installed_units() -> Result<Vec<String>> {
+    let Ok(entries) = fs::read_dir(unit_dir()?) else {
+        return Ok(Vec::new());
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(UNIT_PREFIX) && name.ends_with(".service"))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// The unit for `folder`, its name escaped the way `systemd-escape` does so that
+/// `%I` in the unit gives the folder name back
+fn unit_name(folder: &str) -> String {
+    let mut escaped = String::new();
+    for (i, byte) in folder.bytes().enumerate() {
+        match byte {
+            b'/' => escaped.push('-'),
+            b'.' if i == 0 => escaped.push_str("\\x2e"),
+            b if b.is_ascii_alphanumeric() || b == b':' || b == b'_' || b == b'.' => escaped.push(b as char),
+            b => escaped.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    format!("{}{}.service", UNIT_PREFIX, escaped)
+}
+
+fn systemctl(args: &[&str]) -> Result<()> {
+    let status = Command::new("systemctl").arg("--user").args(args).status()?;
+    if !status.success() {
+        return Err(GitHubSyncError::UnknownError(format!(
+            "'systemctl --user {}' failed ({})",
+            args.join(" "),
+            status
+        )));
+    }
+    Ok(())
+}
+
+fn render_unit(exe: &Path, folder: &str, working_dir: &Path, settings: &FolderSettings) -> String {
+    let exe = quote(&exe.to_string_lossy());
+    format!(
+        "[Unit]
+Description=GitHub Sync ({folder_desc})
+After=network-online.target
+Wants=network-online.target
+
+[Service]
+Type=notify
+NotifyAccess=main
+WorkingDirectory={working_dir}
+ExecStart={exe} start --name {folder} --foreground
+ExecStop={exe} stop
+TimeoutStartSec={start_timeout}
+TimeoutStopSec={stop_timeout}
+WatchdogSec={watchdog}
+Restart=on-failure
+RestartSec=5
+
+[Install]
+WantedBy=default.target
+",
+        folder_desc = escape(folder),
+        working_dir = escape(&working_dir.to_string_lossy()),
+        folder = quote(folder),
+        start_timeout = START_TIMEOUT_SECS,
+        // Time to commit and push pending changes before systemd kills us
+        stop_timeout = settings.shutdown_timeout_secs + 15,
+        watchdog = WATCHDOG_SECS,
+    )
+}
+
+/// Escape systemd specifiers (`%`) and environment expansion (`$`).
+fn escape(value: &str) -> String {
+    value.replace('%', "%%").replace('$', "$$")
+}
+
+/// One command-line word for `ExecStart=`, which splits on whitespace.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", escape(value).replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_unit() {
+        let unit = render_unit(
+            Path::new("/usr/local/bin/ghs"),
+            "notes",
+            Path::new("/home/me"),
+            &FolderSettings::default(),
+        );
+
+        assert!(unit.contains("Type=notify\n"));
+        assert!(unit.contains("WorkingDirectory=/home/me\n"));
+        assert!(unit.contains("ExecStart=\"/usr/local/bin/ghs\" start --name \"notes\" --foreground\n"));
+        assert!(unit.contains("TimeoutStopSec=45\n"));
+        assert!(unit.contains("WantedBy=default.target\n"));
+    }
+
+    #[test]
+    fn test_unit_names() {
+        assert_eq!(unit_name("notes"), "github-sync@notes.service");
+        assert_eq!(unit_name("my notes"), "github-sync@my\\x20notes.service");
+        assert_eq!(unit_name("work/notes"), "github-sync@work-notes.service");
+        assert_eq!(unit_name("a-b"), "github-sync@a\\x2db.service");
+        assert_eq!(unit_name(".hidden"), "github-sync@\\x2ehidden.service");
+        assert_ne!(unit_name("notes"), unit_name("photos"));
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(quote("my notes"), "\"my notes\"");
+        assert_eq!(quote("100%"), "\"100%%\"");
+        assert_eq!(quote("a\"b"), "\"a\\\"b\"");
+        assert_eq!(escape("$HOME"), "$$HOME");
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::Path;
+use chrono::{DateTime, Local};
+use colored::*;
+use crate::config::Config;
+use crate::error::{Result, GitHubSyncError};
+use crate::git::{short_id, GitSync};
+use crate::ipc::{self, Request, Response};
+use crate::lock::FileLock;
+use crate::logger;
+use dirs;
+
+pub fn create(name: String, message: Option<String>) -> Result<()> {
+    let request = Request::Snapshot { name: name.clone(), message: message.clone() };
+    if let Some(response) = ipc::request(&request)? {
+        return report(response);
+    }
+
+    let (_lock, git) = open()?;
+    let snapshot = git.create_snapshot(&name, message.as_deref())?;
+    logger::success(&format!("Created snapshot {} at {}", snapshot.name, short_id(&snapshot.commit)));
+    push(&git);
+    Ok(())
+}
+
+pub fn list(json: bool) -> Result<()> {
+    let sync_root = sync_root();
+    if !sync_root.join(".git").exists() {
+        logger::error("No sync history yet. Use 'ghs start' first.");
+        return Ok(());
+    }
+    // Listing only reads references, so it never needs the daemon or the lock
+    let config = Config::load()?;
+    let snapshots = GitSync::new(&sync_root, &config.remote_url, &config.branch)?.snapshots()?;
+
+    if json {
+        let out = serde_json::to_string_pretty(&snapshots)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Failed to serialize snapshots: {}", e)))?;
+        println!("{}", out);
+        return Ok(());
+    }
+
+    if snapshots.is_empty() {
+        logger::info("No snapshots yet. Create one with 'ghs snapshot create <name>'");
+        return Ok(());
+    }
+
+    println!("\n📌 Snapshots:\n");
+    for snapshot in &snapshots {
+        let time: DateTime<Local> = snapshot.time.into();
+        println!(
+            "{} {} {} {}: {}",
+            snapshot.name.bold(),
+            short_id(&snapshot.commit).yellow(),
+            time.format("%Y-%m-%d %H:%M:%S"),
+            snapshot.device.green(),
+            snapshot.message
+        );
+    }
+    println!();
+    Ok(())
+}
+
+pub fn rollback(name: String, folder: String) -> Result<()> {
+    let request = Request::Rollback { snapshot: name.clone(), folder: folder.clone() };
+    if let Some(response) = ipc::request(&request)? {
+        return report(response);
+    }
+
+    let (_lock, mut git) = open()?;
+    // A rollback can delete many files; it is held for 'ghs confirm' like any other
+    let settings = Config::load()?.folder_settings(&folder);
+    git.set_mass_delete_limits(settings.mass_delete_max_files, settings.mass_delete_max_percent);
+    git.rollback(Path::new(&folder), &name)?;
+    logger::success(&format!("Rolled {} back to snapshot {}", folder, name));
+    push(&git);
+    Ok(())
+}
+
+fn sync_root() -> std::path::PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".github-sync")
+}
+
+/// Open the repository when no daemon is running, holding the folder lock
+fn open() -> Result<(FileLock, GitSync)> {
+    let config = Config::load()?;
+    let sync_root = sync_root();
+
+    // The daemon may be starting up and not answering yet; never share the repository with it
+    let lock = FileLock::folder_or_busy(&sync_root)?;
+    let git = GitSync::open(&sync_root, &config, &config.repository_settings())?;
+    Ok((lock, git))
+}
+
+fn push(git: &GitSync) {
+    logger::sync("Syncing with remote...");
+    if let Err(e) = git.sync() {
+        logger::warn(&format!("Sync failed: {} - the change is pushed with the next sync", e));
+    }
+}
+
+fn report(response: Response) -> Result<()> {
+    logger::success(&response.into_result()?);
+    Ok(())
+}
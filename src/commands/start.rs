@@ -1,27 +1,145 @@
 // src/commands/start.rs
 use std::path::PathBuf;
 use std::fs;
-use crate::config::Config;
-use crate::error::Result;
+use std::time::Duration;
+use crate::config::{self, Config};
+use crate::error::{Result, GitHubSyncError};
 use crate::git::GitSync;
 use crate::github::GitHub;
+use crate::ipc;
+use crate::lock::{self, FileLock};
+use crate::status;
+use crate::systemd;
 use crate::watcher::FileWatcher;
+use crate::worker::SyncWorker;
 use crate::logger;
 use dirs;
 
 const PID_FILE: &str = ".github-sync.pid";
 const MAIN_REPO: &str = "github-sync";
+#[cfg(unix)]
+const DAEMON_LOG: &str = "daemon.log";
+/// How long `--daemon` waits for the initial sync before giving up on the child
+#[cfg(unix)]
+const READY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(600);
+
+/// Start the daemon in the background and return once it is ready.
+///
+/// Rather than forking this process, which already runs the tokio thread pool, the
+/// binary re-executes itself with `--foreground` in a new session. It logs to the
+/// rotating log file like any daemon; its stderr goes to `daemon.log` so a panic
+/// is not lost. The child only opens its control socket after the initial sync
+/// and once the watcher is armed, so a successful status request is the readiness
+/// signal.
+#[cfg(unix)]
+pub async fn spawn_daemon(folder_name: &str, level: Option<logger::Level>, format: logger::Format) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+    use crate::ipc::Request;
+
+    if ipc::request(&Request::Status)?.is_some() {
+        logger::error("GitHub Sync is already running. Use 'ghs stop' first.");
+        return Ok(());
+    }
+
+    let state_dir = config::state_dir()?;
+    let log_path = state_dir.join(logger::LOG_FILE);
+    let stderr = fs::OpenOptions::new().create(true).append(true).open(state_dir.join(DAEMON_LOG))?;
+
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .args(["start", "--name", folder_name, "--foreground"])
+        .args(match level {
+            Some(logger::Level::Debug) => &["--verbose"][..],
+            Some(logger::Level::Warn | logger::Level::Error) => &["--quiet"][..],
+            _ => &[][..],
+        })
+        .args(match format {
+            logger::Format::Json => &["--log-format", "json"][..],
+            logger::Format::Text => &[][..],
+        })
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(stderr);
+    // SAFETY: setsid is async-signal-safe and touches no memory of the parent
+    unsafe {
+        command.pre_exec(|| nix::unistd::setsid().map(|_| ()).map_err(std::io::Error::from));
+    }
+    let mut child = command.spawn()?;
+
+    logger::info(&format!("Starting GitHub Sync in the background (PID: {})...", child.id()));
+    let deadline = Instant::now() + READY_TIMEOUT;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Err(GitHubSyncError::UnknownError(format!(
+                "GitHub Sync exited during startup ({}); see {}",
+                status,
+                log_path.display()
+            )));
+        }
+
+        if let Ok(Some(response)) = ipc::request(&Request::Status) {
+            let offline = response.status.as_ref().and_then(|s| s["offline"].as_bool()).unwrap_or(false);
+            if offline {
+                logger::warn("GitHub Sync started in offline mode; changes are pushed once the remote is reachable");
+            } else {
+                logger::success("GitHub Sync started in the background");
+            }
+            logger::info(&format!("Logs: {}", log_path.display()));
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            return Err(GitHubSyncError::UnknownError(format!(
+                "GitHub Sync did not become ready within {}s; see {}",
+                READY_TIMEOUT.as_secs(),
+                log_path.display()
+            )));
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn spawn_daemon(_folder_name: &str, _level: Option<logger::Level>, _format: logger::Format) -> Result<()> {
+    Err(GitHubSyncError::UnknownError(
+        "--daemon is not supported on this platform; install the Windows service instead".to_string(),
+    ))
+}
 
 pub async fn handle(folder_name: String) -> Result<()> {
-    // Check if already running
-    let pid_file = PathBuf::from(PID_FILE);
-    if pid_file.exists() {
+    // Keep a log that survives the terminal or journal; the console still gets every line
+    logger::set_folder(&folder_name);
+    if let Err(e) = config::state_dir().and_then(|dir| Ok(logger::log_to_file(&dir)?)) {
+        logger::warn(&format!("Logging to the console only: {}", e));
+    }
+
+    // Held until this function returns; a second `ghs start` fails to take it even if
+    // both raced past every other check
+    let Some(_instance_lock) = FileLock::instance()? else {
         logger::error("GitHub Sync is already running. Use 'ghs stop' first.");
         return Ok(());
+    };
+
+    // A PID file alongside a free lock was left by a crash, unless the PID belongs to
+    // an older daemon that predates the lock
+    let pid_file = PathBuf::from(PID_FILE);
+    if let Ok(contents) = fs::read_to_string(&pid_file) {
+        match contents.trim().parse::<u32>() {
+            Ok(pid) if lock::is_running_instance(pid) => {
+                logger::error(&format!("GitHub Sync is already running (PID: {}). Use 'ghs stop' first.", pid));
+                return Ok(());
+            }
+            _ => {
+                logger::warn(&format!("Removing stale PID file left by a previous run ({})", contents.trim()));
+                fs::remove_file(&pid_file)?;
+            }
+        }
     }
 
-    // Write PID file
-    fs::write(&pid_file, std::process::id().to_string())?;
+    // Write PID file; removed again however this function returns
+    let _pid_file = PidFile::create(pid_file)?;
 
     // Initialize GitHub client and ensure main repository exists
     let github = GitHub::new().await?;
@@ -34,40 +152,53 @@ pub async fn handle(folder_name: String) -> Result<()> {
     let folder_path = sync_root.join(&folder_name);
     fs::create_dir_all(&folder_path)?;
 
+    // Keep one-shot commands such as `ghs push` off the repository while we own it
+    let Some(_folder_lock) = FileLock::folder(&sync_root)? else {
+        logger::error(&format!("{} is in use by another GitHub Sync process.", sync_root.display()));
+        return Ok(());
+    };
+
     // Load or create config
     let config = Config::load().unwrap_or(Config {
         remote_url,
         branch: "main".to_string(),
         sync_paths: vec![sync_root.clone()],
         sync_interval: 300, // 5 minutes default
+        folders: Default::default(),
+        log_level: None,
+        trash: Default::default(),
+        history: Default::default(),
+        backups: Default::default(),
+        secrets: Default::default(),
     });
 
     // Initialize Git sync for the main repository
-    let git = GitSync::new(
-        &sync_root,
-        &config.remote_url,
-        &config.branch
-    )?;
+    let settings = config.folder_settings(&folder_name);
+    let git = GitSync::open(&sync_root, &config, &settings)?;
+
+    // From here on the repository belongs to the folder's sync worker thread
+    let worker = SyncWorker::spawn(&folder_name, git)?;
+    let mut watcher = FileWatcher::new(&folder_name, worker, settings, status::status_file()?)?;
 
-    // Start remote watcher
+    // Pull what other devices push without waiting for a local change
     logger::info("Starting remote change watcher...");
-    crate::remote_watcher::start_remote_watcher();
+    watcher.watch_remote(Duration::from_secs(config.sync_interval));
 
-    // Initial sync
+    // Initial sync; without a network the watcher starts offline and keeps retrying
     logger::sync("Performing initial sync...");
-    if let Err(e) = git.sync() {
-        logger::error(&format!("Initial sync failed: {}", e));
-        fs::remove_file(&pid_file)?;
-        return Err(e);
+    if !watcher.sync_now().await && !watcher.is_held() {
+        logger::warn("Initial sync failed, starting in offline mode");
     }
 
-    // Initialize and start file watcher
-    let mut watcher = FileWatcher::new(git)?;
-
     // Watch the entire sync directory
     if let Err(e) = watcher.watch(&sync_root) {
         logger::error(&format!("Failed to watch directory: {}", e));
-        fs::remove_file(&pid_file)?;
+        return Err(e);
+    }
+
+    // Accept requests from `ghs stop`, `ghs status`, etc. on the control socket
+    if let Err(e) = ipc::serve(watcher.control_channel()) {
+        logger::error(&format!("Failed to open control socket: {}", e));
         return Err(e);
     }
 
@@ -81,31 +212,66 @@ pub async fn handle(folder_name: String) -> Result<()> {
         }
     }
 
+    // Under systemd `Type=notify` this is what completes `systemctl start`
+    systemd::ready(&format!("Watching {}", folder_path.display()));
+
     logger::success(&format!(
         "GitHub Sync started successfully!\nYour files will be synced in: {}\nAll files will be pushed to: {}", 
         folder_path.display(),
         config.remote_url
     ));
 
-    // Run the watcher (this blocks indefinitely)
-    let result = watcher.run();
-    
-    // Clean up PID file
-    fs::remove_file(&pid_file)?;
+    // Run the watcher until shutdown is requested and pending changes are flushed
+    let result = watcher.run().await;
     
+    // Clean up the control socket; the PID file goes with `_pid_file`
+    ipc::cleanup();
+
     result
 }
 
+/// The daemon's PID file, for `ghs stop` and `ghs status`; removed when dropped so
+/// that no early return leaves a stale one behind
+struct PidFile(PathBuf);
+
+impl PidFile {
+    fn create(path: PathBuf) -> Result<Self> {
+        fs::write(&path, std::process::id().to_string())?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.0) {
+            logger::warn(&format!("Failed to remove PID file {}: {}", self.0.display(), e));
+        }
+    }
+}
+
 #[cfg(unix)]
-extern "C" fn handle_signal(sig: i32) {
-    let signal = nix::sys::signal::Signal::try_from(sig).unwrap();
-    logger::info(&format!("Received signal: {:?}", signal));
-    logger::info("Shutting down gracefully...");
-    
-    // Clean up PID file
-    if let Err(e) = fs::remove_file(PID_FILE) {
-        logger::error(&format!("Failed to clean up PID file: {}", e));
+extern "C" fn handle_signal(_sig: i32) {
+    // Only async-signal-safe work here: the watcher loop notices the flag, flushes
+    // pending changes and returns, after which `handle` removes the PID file
+    crate::watcher::signal_shutdown();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_pid_file_is_removed_on_every_exit() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join(PID_FILE);
+
+        let failing = || -> Result<()> {
+            let _pid_file = PidFile::create(path.clone())?;
+            assert_eq!(fs::read_to_string(&path)?, std::process::id().to_string());
+            Err(GitHubSyncError::UnknownError("startup failed".to_string()))
+        };
+        assert!(failing().is_err());
+        assert!(!path.exists());
     }
-    
-    std::process::exit(0);
 }
@@ -1,31 +1,109 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use chrono::{DateTime, Local};
-use crate::error::Result;
-use crate::config::Config;
+use colored::*;
+use crate::util::format_duration;
+use crate::error::{Result, GitHubSyncError};
+use crate::config::{Config, SecretAction};
+use crate::git::GitSync;
+use crate::ipc::{self, Request};
+use crate::lock;
 use crate::logger;
+use crate::status::{self, FolderStatus, Status, SyncState};
 
 const PID_FILE: &str = ".github-sync.pid";
-const STATUS_FILE: &str = ".github-sync.status";
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct StatusInfo {
-    last_sync: SystemTime,
-    pending_changes: Vec<String>,
+pub fn handle(json: bool) -> Result<()> {
+    let status = collect()?;
+
+    if json {
+        let out = serde_json::to_string_pretty(&status)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Failed to serialize status: {}", e)))?;
+        println!("{}", out);
+    } else {
+        print_text(&status);
+    }
+    Ok(())
+}
+
+/// Ask the running daemon first; fall back to the files it leaves behind and the
+/// repository in the sync root
+fn collect() -> Result<Status> {
+    let live = match ipc::request(&Request::Status) {
+        Ok(Some(response)) => response.status.and_then(|s| serde_json::from_value::<FolderStatus>(s).ok()),
+        _ => None,
+    };
+
+    let pid = fs::read_to_string(PathBuf::from(PID_FILE))
+        .ok()
+        .and_then(|pid| pid.trim().parse::<u32>().ok());
+    let running = live.is_some() || pid.is_some_and(lock::is_running_instance);
+    let config = Config::load().ok();
+
+    let folders = match live {
+        Some(folder) => vec![folder],
+        None => {
+            let sync_root = dirs::home_dir()
+                .expect("Could not find home directory")
+                .join(".github-sync");
+            let mut folders = last_reported(running).map_or_else(|| synced_folders(&sync_root), |f| vec![f]);
+
+            // The daemon is not answering, so what it reported may be out of date
+            if let Some(config) = &config
+                && sync_root.join(".git").exists()
+            {
+                let git = GitSync::new(&sync_root, &config.remote_url, &config.branch)?;
+                for folder in &mut folders {
+                    folder.fill_git(&git);
+                }
+            }
+            folders
+        }
+    };
+
+    Ok(Status {
+        running,
+        pid: pid.filter(|_| running),
+        remote: config.as_ref().map(|c| c.remote_url.clone()),
+        branch: config.as_ref().map(|c| c.branch.clone()),
+        folders,
+    })
 }
 
-pub fn handle() -> Result<()> {
+/// What the last daemon wrote to the status file
+fn last_reported(running: bool) -> Option<FolderStatus> {
+    let content = fs::read_to_string(status::status_file().ok()?).ok()?;
+    let mut status = serde_json::from_str::<FolderStatus>(&content).ok()?;
+    if !running {
+        status.state = SyncState::Stopped;
+    }
+    Some(status)
+}
+
+/// Every folder created with `ghs start -n <name>`, one directory each in the sync root
+fn synced_folders(sync_root: &Path) -> Vec<FolderStatus> {
+    let Ok(entries) = fs::read_dir(sync_root) else {
+        return Vec::new();
+    };
+    let mut folders: Vec<FolderStatus> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir() && !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| FolderStatus::stopped(entry.file_name().to_string_lossy().into_owned(), entry.path()))
+        .collect();
+    folders.sort_by(|a, b| a.name.cmp(&b.name));
+    folders
+}
+
+fn print_text(status: &Status) {
     println!("\n{}", "GitHub Sync Status".bold());
     println!("{}", "=".repeat(50));
 
     // Check if service is running
-    let pid_file = PathBuf::from(PID_FILE);
-    if pid_file.exists() {
-        let pid = fs::read_to_string(&pid_file)?.trim().to_string();
-        logger::success(&format!("Service is running (PID: {})", pid));
-    } else {
-        logger::error("Service is not running");
+    match (status.running, status.pid) {
+        (true, Some(pid)) => logger::success(&format!("Service is running (PID: {})", pid)),
+        (true, None) => logger::success("Service is running"),
+        (false, _) => logger::error("Service is not running"),
     }
 
     // Load and show configuration
@@ -35,7 +113,7 @@ pub fn handle() -> Result<()> {
             println!("Remote: {}", config.remote_url);
             println!("Branch: {}", config.branch);
             println!("Sync interval: {}s", config.sync_interval);
-            
+
             println!("\n{}", "Watched Paths:".bold());
             if config.sync_paths.is_empty() {
                 println!("- Current directory (.)");
@@ -50,73 +128,97 @@ pub fn handle() -> Result<()> {
         }
     }
 
-    // Show sync status if available
-    let status_file = PathBuf::from(STATUS_FILE);
-    if status_file.exists() {
-        match fs::read_to_string(&status_file) {
-            Ok(content) => {
-                if let Ok(status: StatusInfo) = serde_json::from_str(&content) {
-                    let last_sync: DateTime<Local> = status.last_sync.into();
-                    
-                    println!("\n{}", "Sync Status:".bold());
-                    println!("Last sync: {}", last_sync.format("%Y-%m-%d %H:%M:%S"));
-                    
-                    if !status.pending_changes.is_empty() {
-                        println!("\n{}", "Pending Changes:".bold());
-                        for change in status.pending_changes {
-                            println!("- {}", change);
-                        }
-                    }
-                }
-            }
-            Err(_) => {
-                logger::warn("Could not read sync status");
+    for folder in &status.folders {
+        print_folder(folder);
+    }
+
+    println!(); // Add final newline
+}
+
+fn print_folder(status: &FolderStatus) {
+    println!("\n{}", format!("Folder '{}':", status.name).bold());
+    println!("Path: {}", status.path.display());
+    println!("State: {}", status.state.as_str());
+    match status.last_sync {
+        Some(last_sync) => println!("Last sync: {}", format_time(last_sync)),
+        None => println!("Last sync: never"),
+    }
+
+    if status.paused {
+        match status.paused_until.map(|until| until.duration_since(SystemTime::now())) {
+            Some(Ok(remaining)) => {
+                logger::warn(&format!("Syncing is paused (resumes in {})", format_duration(remaining)));
             }
+            Some(Err(_)) => logger::warn("Syncing is paused (resuming now)"),
+            None => logger::warn("Syncing is paused until 'ghs resume'"),
         }
     }
 
+    if let Some(deletion) = status.mass_deletion {
+        logger::error(&format!(
+            "Push held: {} of {} files deleted. Check the folder, then run 'ghs confirm' to push the deletions",
+            deletion.deleted, deletion.tracked
+        ));
+    }
+
+    if !status.secrets.is_empty() {
+        logger::error(&format!("{} files look like they contain secrets and were not synced:", status.secrets.len()));
+        for finding in &status.secrets {
+            let action = match finding.action {
+                SecretAction::Block => "blocked",
+                SecretAction::Quarantine => "quarantined",
+            };
+            println!("- {} ({}, line {}, {})", finding.path.display(), finding.rule, finding.line, action);
+        }
+    }
+
+    if status.offline {
+        logger::warn(&format!("Offline - {} commits queued locally", status.queued_commits));
+        if let Some(next_retry) = status.next_retry {
+            println!("Next retry: {}", format_time(next_retry));
+        }
+    }
+    if let Some(error) = &status.last_error {
+        println!("Last error: {}", error);
+    }
+
     // Show Git status
-    if pid_file.exists() {
-        match get_git_status() {
-            Ok((ahead, behind)) => {
-                println!("\n{}", "Git Status:".bold());
-                if ahead > 0 {
-                    println!("Commits ahead of remote: {}", ahead);
-                }
-                if behind > 0 {
-                    println!("Commits behind remote: {}", behind);
-                }
-                if ahead == 0 && behind == 0 {
-                    logger::success("In sync with remote");
-                }
+    println!("\n{}", "Git Status:".bold());
+    match (status.ahead, status.behind) {
+        (Some(0), Some(0)) => logger::success("In sync with remote"),
+        (Some(ahead), Some(behind)) => {
+            if ahead > 0 {
+                println!("Commits ahead of remote: {}", ahead);
             }
-            Err(_) => {
-                logger::warn("Could not get Git status");
+            if behind > 0 {
+                println!("Commits behind remote: {}", behind);
             }
         }
+        _ => println!("Not fetched from remote yet"),
+    }
+    if let Some(uncommitted) = status.uncommitted {
+        println!("Uncommitted changes: {}", uncommitted);
+    }
+    if let Some(last_fetch) = status.last_fetch {
+        println!("Last fetch: {}", format_time(last_fetch));
     }
 
-    println!(); // Add final newline
-    Ok(())
+    if !status.conflicts.is_empty() {
+        println!("\n{}", "Conflicts (local history kept in backup branches):".bold());
+        for branch in &status.conflicts {
+            println!("- {}", branch);
+        }
+    }
+
+    if !status.pending_paths.is_empty() {
+        println!("\n{}", "Pending Changes:".bold());
+        for change in &status.pending_paths {
+            println!("- {}", change);
+        }
+    }
 }
 
-fn get_git_status() -> Result<(usize, usize)> {
-    use git2::{Repository, BranchType};
-    
-    let repo = Repository::open(".")?;
-    let head = repo.head()?;
-    let branch_name = head.shorthand().unwrap_or("HEAD");
-    
-    // Get local and remote commits
-    let local = head.target().ok_or_else(|| {
-        crate::error::JugaadError::GitError(git2::Error::from_str("Could not get local HEAD"))
-    })?;
-    
-    let remote_branch = repo.find_branch(&format!("origin/{}", branch_name), BranchType::Remote)?;
-    let remote = remote_branch.get().target().ok_or_else(|| {
-        crate::error::JugaadError::GitError(git2::Error::from_str("Could not get remote HEAD"))
-    })?;
-    
-    let (ahead, behind) = repo.graph_ahead_behind(local, remote)?;
-    Ok((ahead, behind))
-}
\ No newline at end of file
+fn format_time(time: SystemTime) -> String {
+    let time: DateTime<Local> = time.into();
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
@@ -1,12 +1,30 @@
 use std::fs;
 use std::path::PathBuf;
 use crate::error::Result;
+use crate::ipc::{self, Request};
+use crate::lock;
 use crate::logger;
 
 const PID_FILE: &str = ".github-sync.pid";
 
-pub fn handle() -> Result<()> {
+pub fn handle(no_flush: bool) -> Result<()> {
+    // Ask the running daemon to shut down over the control socket. It answers once
+    // pending changes are committed and, unless `no_flush`, pushed.
+    if !no_flush {
+        logger::info("Waiting for pending changes to be pushed...");
+    }
+    if let Some(response) = ipc::request(&Request::Shutdown { flush: !no_flush })? {
+        let message = response.into_result()?;
+        logger::success("GitHub Sync stopped successfully.");
+        logger::info(&message);
+        return Ok(());
+    }
+
+    // No control socket: fall back to signalling the PID from the PID file
     let pid_file = PathBuf::from(PID_FILE);
+    if no_flush {
+        logger::warn("Daemon is not reachable over the control socket; --no-flush is ignored");
+    }
     
     if !pid_file.exists() {
         logger::info("GitHub Sync is not running.");
@@ -15,11 +33,18 @@ pub fn handle() -> Result<()> {
 
     // Read PID from file
     let pid_str = fs::read_to_string(&pid_file)?;
-    let pid: i32 = pid_str.trim().parse().map_err(|_| {
+    let Ok(pid) = pid_str.trim().parse::<i32>() else {
         fs::remove_file(&pid_file)?;
         logger::error("Invalid PID file found, cleaned up.");
         return Ok(());
-    })?;
+    };
+
+    // Never signal a process that merely inherited the PID of a crashed daemon
+    if !lock::is_running_instance(pid as u32) {
+        fs::remove_file(&pid_file)?;
+        logger::info("GitHub Sync is not running (removed stale PID file).");
+        return Ok(());
+    }
 
     // Send SIGTERM to the process
     #[cfg(unix)]
@@ -29,8 +54,8 @@ pub fn handle() -> Result<()> {
 
         match signal::kill(Pid::from_raw(pid), Signal::SIGTERM) {
             Ok(_) => {
-                logger::success("GitHub Sync stopped successfully.");
-                fs::remove_file(&pid_file)?;
+                // The daemon flushes pending changes and removes the PID file itself
+                logger::success("Stop signal sent; GitHub Sync exits once pending changes are pushed.");
             }
             Err(e) => {
                 logger::error(&format!("Failed to stop GitHub Sync: {}", e));
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{DateTime, Local};
+use colored::*;
+use crate::error::{Result, GitHubSyncError};
+use crate::logger;
+use crate::trash::Trash;
+use dirs;
+
+fn sync_root() -> PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".github-sync")
+}
+
+pub fn list(json: bool) -> Result<()> {
+    let entries = Trash::new(&sync_root()).entries()?;
+
+    if json {
+        let out = serde_json::to_string_pretty(&entries)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Failed to serialize trash: {}", e)))?;
+        println!("{}", out);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        logger::info("The trash is empty");
+        return Ok(());
+    }
+
+    println!("\n🗑  Files deleted by pulls:\n");
+    for entry in &entries {
+        let time: DateTime<Local> = entry.deleted_at.into();
+        println!(
+            "{} {} {} ({} bytes)",
+            entry.id.yellow(),
+            time.format("%Y-%m-%d %H:%M:%S"),
+            entry.path.display(),
+            entry.size
+        );
+    }
+    println!("\nRestore one with 'ghs trash restore <id or path>'\n");
+    Ok(())
+}
+
+pub fn restore(item: &str, output: Option<PathBuf>) -> Result<()> {
+    let sync_root = sync_root();
+    let trash = Trash::new(&sync_root);
+    let Some(entry) = trash.find(item)? else {
+        logger::error(&format!("Nothing in the trash matches '{}'", item));
+        return Ok(());
+    };
+
+    let dest = output.unwrap_or_else(|| sync_root.join(&entry.path));
+    if dest.exists() {
+        return Err(GitHubSyncError::RestoreError(format!(
+            "{} already exists; pass --output to restore elsewhere",
+            dest.display()
+        )));
+    }
+
+    trash.restore(&entry, &dest)?;
+    logger::success(&format!("Restored {} to {}", entry.path.display(), dest.display()));
+    Ok(())
+}
+
+pub fn empty(older_than: Option<Duration>) -> Result<()> {
+    let removed = Trash::new(&sync_root()).empty(older_than)?;
+    logger::success(&format!("Removed {} files from the trash", removed));
+    Ok(())
+}
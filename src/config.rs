@@ -1,16 +1,198 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use serde::{Serialize, Deserialize};
 use crate::error::{Result, GitHubSyncError};
+use crate::logger::Level;
 
 const CONFIG_FILE: &str = ".github-sync.json";
 
+/// Directory for daemon runtime state (control socket, logs, locks), created on demand.
+pub fn state_dir() -> Result<PathBuf> {
+    let dir = dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .ok_or_else(|| GitHubSyncError::ConfigError("Could not find state directory".to_string()))?
+        .join("github-sync");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub remote_url: String,
     pub branch: String,
     pub sync_paths: Vec<PathBuf>,
     pub sync_interval: u64,
+    /// Per-folder overrides, keyed by the folder name passed to `ghs start -n`
+    #[serde(default)]
+    pub folders: HashMap<String, FolderSettings>,
+    /// One of error, warn, info or debug; `--verbose`/`--quiet` override it
+    #[serde(default)]
+    pub log_level: Option<Level>,
+    #[serde(default)]
+    pub trash: TrashSettings,
+    #[serde(default)]
+    pub history: HistorySettings,
+    #[serde(default)]
+    pub backups: BackupSettings,
+    #[serde(default)]
+    pub secrets: SecretSettings,
+}
+
+/// Tuning knobs for a single synced folder. Missing fields fall back to the defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FolderSettings {
+    /// Quiet period after the last file event before a batch is synced
+    pub debounce_ms: u64,
+    /// Minimum time between two syncs; batches ready sooner are deferred, not dropped
+    pub min_sync_gap_secs: u64,
+    /// Longest a change may wait while events keep arriving before a sync is forced
+    pub max_batch_latency_secs: u64,
+    /// How file events are received
+    pub watch_mode: WatchMode,
+    /// Scan interval when polling instead of using native notifications
+    pub poll_interval_secs: u64,
+    /// Interval for a full rescan against the git index to catch missed events (0 disables)
+    pub rescan_interval_secs: u64,
+    /// Filesystem types that are polled when `watch_mode` is `auto`
+    pub poll_filesystems: Vec<String>,
+    /// First retry delay after a failed sync; doubles on every further failure
+    pub retry_initial_secs: u64,
+    /// Upper bound for the retry delay while offline
+    pub retry_max_secs: u64,
+    /// How long shutdown may spend pushing pending commits
+    pub shutdown_timeout_secs: u64,
+    /// Hold pushes that delete more than this many files until `ghs confirm` (0 disables)
+    pub mass_delete_max_files: usize,
+    /// Hold pushes that delete more than this percentage of the tracked files (0 disables)
+    pub mass_delete_max_percent: usize,
+}
+
+/// Retention for the local trash that keeps files deleted by pulls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashSettings {
+    /// Files are removed this many days after they were trashed (0 keeps them)
+    pub max_age_days: u64,
+    /// Once the trash is larger than this, the oldest files go first (0 disables)
+    pub max_size_mb: u64,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self {
+            max_age_days: 30,
+            max_size_mb: 1_024,
+        }
+    }
+}
+
+/// How `ghs gc` thins out old history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// Every commit younger than this is kept
+    pub keep_all_days: u64,
+    /// Up to this age the last commit of each hour is kept; older history keeps one per day
+    pub keep_hourly_days: u64,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            keep_all_days: 7,
+            keep_hourly_days: 30,
+        }
+    }
+}
+
+/// Retention for the backup branches that keep local history a conflicting pull replaced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Backups older than this many days are deleted (0 keeps them)
+    pub keep_days: u64,
+    /// Only this many of the newest backups are kept (0 keeps all)
+    pub keep_count: usize,
+    /// Also push backups to the remote under `refs/ghs/backups/<device>/`
+    pub push: bool,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            keep_days: 30,
+            keep_count: 20,
+            push: false,
+        }
+    }
+}
+
+/// Scanning of changed files for credentials before they are committed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretSettings {
+    pub enabled: bool,
+    /// What happens to a file that looks like it holds a secret
+    pub action: SecretAction,
+    /// Paths that are never scanned, as git pathspecs such as `docs/*.md`
+    pub allow_paths: Vec<String>,
+    /// Regular expressions for matches that are not secrets, such as example keys
+    pub allow_patterns: Vec<String>,
+}
+
+impl Default for SecretSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action: SecretAction::Block,
+            allow_paths: Vec::new(),
+            allow_patterns: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretAction {
+    /// Leave the file where it is, unsynced, until the secret is removed
+    Block,
+    /// Move the file to `.ghs-quarantine` in the sync root, which never syncs
+    Quarantine,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchMode {
+    /// Native notifications, falling back to polling on network filesystems or watch failures
+    Auto,
+    /// Native notifications only
+    Native,
+    /// Always poll
+    Poll,
+}
+
+impl Default for FolderSettings {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 2_000,
+            min_sync_gap_secs: 5,
+            max_batch_latency_secs: 60,
+            watch_mode: WatchMode::Auto,
+            poll_interval_secs: 5,
+            rescan_interval_secs: 300,
+            poll_filesystems: ["nfs", "nfs4", "cifs", "smb3", "smbfs", "fuse.sshfs", "9p"]
+                .iter()
+                .map(|fs| fs.to_string())
+                .collect(),
+            retry_initial_secs: 5,
+            retry_max_secs: 300,
+            shutdown_timeout_secs: 30,
+            mass_delete_max_files: 50,
+            mass_delete_max_percent: 20,
+        }
+    }
 }
 
 impl Config {
@@ -35,7 +217,26 @@ impl Config {
         Ok(())
     }
 
-    pub fn is_configured(&self) -> bool {
-        !self.remote_url.is_empty() && !self.branch.is_empty()
+    /// Settings for `folder`, falling back to the defaults when it has no entry.
+    pub fn folder_settings(&self, folder: &str) -> FolderSettings {
+        self.folders.get(folder).cloned().unwrap_or_default()
+    }
+
+    /// Settings for commands that work on the whole sync root rather than one
+    /// folder: the defaults, with the strictest mass-deletion limits any folder sets.
+    pub fn repository_settings(&self) -> FolderSettings {
+        let strictest = |limit: fn(&FolderSettings) -> usize| {
+            let defaults = limit(&FolderSettings::default());
+            self.folders.values().map(limit).fold(defaults, |a, b| match (a, b) {
+                (0, b) => b,
+                (a, 0) => a,
+                (a, b) => a.min(b),
+            })
+        };
+        FolderSettings {
+            mass_delete_max_files: strictest(|s| s.mass_delete_max_files),
+            mass_delete_max_percent: strictest(|s| s.mass_delete_max_percent),
+            ..FolderSettings::default()
+        }
     }
 }
\ No newline at end of file
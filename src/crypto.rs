@@ -0,0 +1,647 @@
+// src/crypto.rs
+//
+// Client-side encryption of what is pushed. The local repository stays plaintext,
+// so history, restore, trash and the watcher work as before; only the commits
+// that cross to the remote are sealed. Sealing is deterministic (the nonce is an
+// HMAC of the content), so a commit seals to the same object on every device and
+// opening a sealed commit gives back the commit it was made from.
+//
+// Author and committer names, emails and timestamps, reference names (snapshot
+// tags, backup branches) and the shape of the history stay in cleartext.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use git2::{ObjectType, Oid, Repository};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use crate::error::{Result, GitHubSyncError};
+
+/// Key file in the git directory. Kept with the repository rather than in the
+/// config so every `GitSync`, daemon or one-off command, seals what it pushes.
+const KEY_FILE: &str = "ghs-key";
+/// Sealed <-> plaintext object ids already translated, one "plain sealed" pair a line
+const MAP_FILE: &str = "ghs-sealed";
+/// Prefix of exported keys
+const KEY_PREFIX: &str = "ghs-key-";
+/// Exported keys without and with the passphrase parameters
+const KEY_VERSION: u8 = 1;
+const KEY_VERSION_KDF: u8 = 2;
+/// First line of every sealed commit and tag message
+pub const SEALED_HEADER: &str = "ghs-encrypted v1";
+/// Leads every sealed blob, so plaintext is never mistaken for ciphertext
+const BLOB_MAGIC: &[u8] = b"GHSENC1\n";
+const NONCE_LEN: usize = 24;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(key).expect("HMAC takes keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+fn error(message: impl Into<String>) -> GitHubSyncError {
+    GitHubSyncError::EncryptionError(message.into())
+}
+
+/// How a key was derived from its passphrase: Argon2id with these costs and salt.
+/// Not secret; every sealed commit carries it so the passphrase alone sets up
+/// another device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kdf {
+    memory_kib: u32,
+    iterations: u32,
+    lanes: u32,
+    salt: [u8; 16],
+}
+
+impl Kdf {
+    /// Argon2's recommended costs with a fresh salt
+    fn generate() -> Result<Self> {
+        let mut salt = [0u8; 16];
+        getrandom::getrandom(&mut salt).map_err(|e| error(format!("No randomness available: {}", e)))?;
+        let params = argon2::Params::default();
+        Ok(Self { memory_kib: params.m_cost(), iterations: params.t_cost(), lanes: params.p_cost(), salt })
+    }
+
+    fn derive(&self, passphrase: &str) -> Result<[u8; 32]> {
+        let params = argon2::Params::new(self.memory_kib, self.iterations, self.lanes, Some(32))
+            .map_err(|e| error(format!("Invalid key derivation parameters: {}", e)))?;
+        let mut master = [0u8; 32];
+        argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &self.salt, &mut master)
+            .map_err(|e| error(format!("Key derivation failed: {}", e)))?;
+        Ok(master)
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(28);
+        for cost in [self.memory_kib, self.iterations, self.lanes] {
+            bytes.extend_from_slice(&cost.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.salt);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let cost = |i: usize| Some(u32::from_le_bytes(bytes.get(i * 4..i * 4 + 4)?.try_into().ok()?));
+        Some(Self {
+            memory_kib: cost(0)?,
+            iterations: cost(1)?,
+            lanes: cost(2)?,
+            salt: bytes.get(12..)?.try_into().ok()?,
+        })
+    }
+
+    /// The `kdf:` line of a sealed message, e.g. `argon2id m=19456 t=2 p=1 salt=...`
+    fn header(&self) -> String {
+        format!(
+            "argon2id m={} t={} p={} salt={}",
+            self.memory_kib, self.iterations, self.lanes, URL_SAFE_NO_PAD.encode(self.salt)
+        )
+    }
+
+    fn parse_header(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        if fields.next()? != "argon2id" {
+            return None;
+        }
+        let mut kdf = Self { memory_kib: 0, iterations: 0, lanes: 0, salt: [0; 16] };
+        for field in fields {
+            match field.split_once('=')? {
+                ("m", value) => kdf.memory_kib = value.parse().ok()?,
+                ("t", value) => kdf.iterations = value.parse().ok()?,
+                ("p", value) => kdf.lanes = value.parse().ok()?,
+                ("salt", value) => kdf.salt = URL_SAFE_NO_PAD.decode(value).ok()?.try_into().ok()?,
+                _ => {}
+            }
+        }
+        Some(kdf)
+    }
+}
+
+/// A 256-bit key and whether file names are sealed too
+#[derive(Clone)]
+pub struct Key {
+    master: [u8; 32],
+    pub encrypt_names: bool,
+    /// Known for keys made from a passphrase; keys exported before it was
+    /// recorded lack it
+    kdf: Option<Kdf>,
+}
+
+impl Key {
+    /// Derive a new key from a passphrase with Argon2id and a random salt
+    pub fn from_passphrase(passphrase: &str, encrypt_names: bool) -> Result<Self> {
+        let kdf = Kdf::generate()?;
+        Ok(Self { master: kdf.derive(passphrase)?, encrypt_names, kdf: Some(kdf) })
+    }
+
+    /// Derive the key a sealed commit was made with from its passphrase, using
+    /// the parameters the commit records
+    pub fn from_sealed_message(message: &[u8], passphrase: &str) -> Result<Self> {
+        let header = SealedHeader::parse(message)?;
+        let kdf = header.kdf.ok_or_else(|| {
+            error("The remote does not record how its key was derived; import the key from 'ghs encrypt export' instead")
+        })?;
+        let key = Self { master: kdf.derive(passphrase)?, encrypt_names: header.names, kdf: Some(kdf) };
+        if header.key_id.is_some_and(|id| id != key.id()) {
+            return Err(error("The passphrase does not match the remote's key"));
+        }
+        Ok(key)
+    }
+
+    /// The key as one line to copy to another device
+    pub fn export(&self) -> String {
+        let version = if self.kdf.is_some() { KEY_VERSION_KDF } else { KEY_VERSION };
+        let mut payload = vec![version, u8::from(self.encrypt_names)];
+        payload.extend_from_slice(&self.master);
+        if let Some(kdf) = self.kdf {
+            payload.extend(kdf.to_bytes());
+        }
+        format!("{}{}", KEY_PREFIX, URL_SAFE_NO_PAD.encode(payload))
+    }
+
+    pub fn import(exported: &str) -> Result<Self> {
+        let payload = exported.trim()
+            .strip_prefix(KEY_PREFIX)
+            .and_then(|encoded| URL_SAFE_NO_PAD.decode(encoded).ok())
+            .ok_or_else(|| error("Not a key exported by 'ghs encrypt export'"))?;
+        let invalid = || error("Not a key exported by 'ghs encrypt export'");
+        let (version, names, rest) = match payload.as_slice() {
+            [version, names @ (0 | 1), rest @ ..] if rest.len() >= 32 => (*version, *names == 1, rest),
+            _ => return Err(invalid()),
+        };
+        let (master, kdf) = rest.split_at(32);
+        let kdf = match version {
+            KEY_VERSION if kdf.is_empty() => None,
+            KEY_VERSION_KDF => Some(Kdf::from_bytes(kdf).ok_or_else(invalid)?),
+            _ => return Err(invalid()),
+        };
+        Ok(Self { master: master.try_into().expect("length checked"), encrypt_names: names, kdf })
+    }
+
+    /// Whether `input` looks like an exported key rather than a passphrase
+    pub fn is_exported(input: &str) -> bool {
+        input.trim().starts_with(KEY_PREFIX)
+    }
+
+    /// Short public fingerprint, recorded in sealed commits to detect a wrong key
+    pub fn id(&self) -> String {
+        hmac(&self.master, &[b"ghs key id"])[..8].iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn load(git_dir: &Path) -> Result<Option<Self>> {
+        match fs::read_to_string(git_dir.join(KEY_FILE)) {
+            Ok(content) => Self::import(&content).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Forget the key and every translation made with it
+    pub fn remove(git_dir: &Path) -> Result<()> {
+        for file in [KEY_FILE, MAP_FILE] {
+            match fs::remove_file(git_dir.join(file)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the key file, readable only by the owner
+    pub fn save(&self, git_dir: &Path) -> Result<()> {
+        let path = git_dir.join(KEY_FILE);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path)?;
+        file.write_all(format!("{}\n", self.export()).as_bytes())?;
+        Ok(())
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(&hmac(&self.master, &[b"ghs encryption key"]).into())
+    }
+
+    fn nonce(&self, domain: &[u8], data: &[u8]) -> [u8; NONCE_LEN] {
+        let mac = hmac(&hmac(&self.master, &[b"ghs nonce key"]), &[domain, b"\0", data]);
+        mac[..NONCE_LEN].try_into().expect("HMAC-SHA256 is longer than a nonce")
+    }
+
+    fn seal_bytes(&self, domain: &[u8], data: &[u8]) -> Vec<u8> {
+        let nonce = self.nonce(domain, data);
+        let mut out = nonce.to_vec();
+        out.extend(self.cipher().encrypt(XNonce::from_slice(&nonce), data).expect("encrypting to memory cannot fail"));
+        out
+    }
+
+    fn open_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < NONCE_LEN {
+            return Err(error("Sealed data is truncated"));
+        }
+        let (nonce, sealed) = data.split_at(NONCE_LEN);
+        self.cipher()
+            .decrypt(XNonce::from_slice(nonce), sealed)
+            .map_err(|_| error("Sealed data does not decrypt with this key"))
+    }
+
+    pub fn seal_blob(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = BLOB_MAGIC.to_vec();
+        out.extend(self.seal_bytes(b"blob", data));
+        out
+    }
+
+    pub fn open_blob(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let sealed = data.strip_prefix(BLOB_MAGIC).ok_or_else(|| error("The remote has an unencrypted file"))?;
+        self.open_bytes(sealed)
+    }
+
+    pub fn seal_name(&self, name: &[u8]) -> String {
+        URL_SAFE_NO_PAD.encode(self.seal_bytes(b"name", name))
+    }
+
+    pub fn open_name(&self, name: &str) -> Result<Vec<u8>> {
+        let sealed = URL_SAFE_NO_PAD.decode(name).map_err(|_| error(format!("'{}' is not an encrypted name", name)))?;
+        self.open_bytes(&sealed)
+    }
+
+    /// A commit or tag message that only says which key sealed it, and how the
+    /// key is derived from its passphrase
+    fn seal_message(&self, message: &[u8], names: bool) -> String {
+        let kdf = self.kdf.map(|kdf| format!("kdf: {}\n", kdf.header())).unwrap_or_default();
+        format!(
+            "{}\nkey: {}\nnames: {}\n{}\n{}\n",
+            SEALED_HEADER,
+            self.id(),
+            if names { "yes" } else { "no" },
+            kdf,
+            STANDARD.encode(self.seal_bytes(b"message", message))
+        )
+    }
+
+    /// The original message and whether names were sealed
+    fn open_message(&self, message: &[u8]) -> Result<(Vec<u8>, bool)> {
+        let header = SealedHeader::parse(message)?;
+        if let Some(id) = header.key_id
+            && id != self.id()
+        {
+            return Err(error(format!("The remote was encrypted with key {}, not this device's {}", id, self.id())));
+        }
+        let sealed = STANDARD.decode(header.body.trim()).map_err(|_| error("Sealed message is not base64"))?;
+        Ok((self.open_bytes(&sealed)?, header.names))
+    }
+}
+
+/// The cleartext lines of a sealed message, and the sealed body after them
+struct SealedHeader<'a> {
+    key_id: Option<&'a str>,
+    names: bool,
+    kdf: Option<Kdf>,
+    body: &'a str,
+}
+
+impl<'a> SealedHeader<'a> {
+    fn parse(message: &'a [u8]) -> Result<Self> {
+        let message = std::str::from_utf8(message).unwrap_or_default();
+        let mut lines = message.lines();
+        if lines.next() != Some(SEALED_HEADER) {
+            return Err(error("The remote has an unencrypted commit"));
+        }
+        let mut header = Self { key_id: None, names: false, kdf: None, body: "" };
+        for line in lines.by_ref() {
+            if let Some(id) = line.strip_prefix("key: ") {
+                header.key_id = Some(id);
+            }
+            if let Some(flag) = line.strip_prefix("names: ") {
+                header.names = flag == "yes";
+            }
+            if let Some(kdf) = line.strip_prefix("kdf: ") {
+                header.kdf = Kdf::parse_header(kdf);
+            }
+            if line.is_empty() {
+                break;
+            }
+        }
+        header.body = lines.next().unwrap_or_default();
+        Ok(header)
+    }
+}
+
+/// Whether a message was written by `Key::seal_message`
+pub fn is_sealed(message: &[u8]) -> bool {
+    message.starts_with(SEALED_HEADER.as_bytes())
+}
+
+/// Translates objects between the local plaintext repository and the sealed ones
+/// the remote holds, remembering every translation so each object is sealed once.
+pub struct Encryption {
+    key: Key,
+    map_file: PathBuf,
+    sealed: RefCell<HashMap<Oid, Oid>>,
+    opened: RefCell<HashMap<Oid, Oid>>,
+}
+
+impl Encryption {
+    /// Encryption for the repository in `git_dir`, or `None` if it has no key
+    pub fn load(git_dir: &Path) -> Result<Option<Self>> {
+        let Some(key) = Key::load(git_dir)? else {
+            return Ok(None);
+        };
+        let encryption = Self::new(key, git_dir);
+        let content = match fs::read_to_string(&encryption.map_file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        for line in content.lines() {
+            if let Some((plain, sealed)) = line.split_once(' ')
+                && let (Ok(plain), Ok(sealed)) = (Oid::from_str(plain), Oid::from_str(sealed))
+            {
+                encryption.sealed.borrow_mut().insert(plain, sealed);
+                encryption.opened.borrow_mut().insert(sealed, plain);
+            }
+        }
+        Ok(Some(encryption))
+    }
+
+    pub fn new(key: Key, git_dir: &Path) -> Self {
+        Self {
+            key,
+            map_file: git_dir.join(MAP_FILE),
+            sealed: RefCell::new(HashMap::new()),
+            opened: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    fn remember(&self, pairs: &mut Vec<(Oid, Oid)>, plain: Oid, sealed: Oid) {
+        self.sealed.borrow_mut().insert(plain, sealed);
+        self.opened.borrow_mut().insert(sealed, plain);
+        pairs.push((plain, sealed));
+    }
+
+    fn save(&self, pairs: &[(Oid, Oid)]) -> Result<()> {
+        if pairs.is_empty() {
+            return Ok(());
+        }
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.map_file)?;
+        let lines: String = pairs.iter().map(|(plain, sealed)| format!("{} {}\n", plain, sealed)).collect();
+        file.write_all(lines.as_bytes())?;
+        Ok(())
+    }
+
+    /// The sealed counterpart of a local commit or tag, created if needed
+    pub fn seal(&self, repo: &Repository, id: Oid) -> Result<Oid> {
+        let mut pairs = Vec::new();
+        let sealed = self.seal_object(repo, id, &mut pairs);
+        self.save(&pairs)?;
+        sealed
+    }
+
+    /// The local counterpart of a commit or tag fetched from the remote
+    pub fn open(&self, repo: &Repository, id: Oid) -> Result<Oid> {
+        let mut pairs = Vec::new();
+        let opened = self.open_object(repo, id, &mut pairs);
+        self.save(&pairs)?;
+        opened
+    }
+
+    fn seal_object(&self, repo: &Repository, id: Oid, pairs: &mut Vec<(Oid, Oid)>) -> Result<Oid> {
+        if let Some(sealed) = self.sealed.borrow().get(&id) {
+            return Ok(*sealed);
+        }
+        let object = repo.find_object(id, None)?;
+        let sealed = match object.kind() {
+            Some(ObjectType::Commit) => return self.translate_commits(repo, id, pairs, true),
+            Some(ObjectType::Tag) => {
+                let tag = object.peel_to_tag()?;
+                let target = repo.find_object(self.seal_object(repo, tag.target_id(), pairs)?, None)?;
+                let tagger = tag.tagger().map_or_else(|| repo.signature(), |t| Ok(t.to_owned()))?;
+                let message = self.key.seal_message(tag.message_bytes().unwrap_or_default(), false);
+                repo.tag_annotation_create(tag.name().unwrap_or_default(), &target, &tagger, &message)?
+            }
+            Some(ObjectType::Tree) => self.translate_tree(repo, id, pairs, true, self.key.encrypt_names)?,
+            Some(ObjectType::Blob) => repo.blob(&self.key.seal_blob(object.peel_to_blob()?.content()))?,
+            _ => return Err(error(format!("Cannot encrypt object {}", id))),
+        };
+        self.remember(pairs, id, sealed);
+        Ok(sealed)
+    }
+
+    fn open_object(&self, repo: &Repository, id: Oid, pairs: &mut Vec<(Oid, Oid)>) -> Result<Oid> {
+        if let Some(opened) = self.opened.borrow().get(&id) {
+            return Ok(*opened);
+        }
+        let object = repo.find_object(id, None)?;
+        let opened = match object.kind() {
+            Some(ObjectType::Commit) => return self.translate_commits(repo, id, pairs, false),
+            Some(ObjectType::Tag) => {
+                let tag = object.peel_to_tag()?;
+                let target = repo.find_object(self.open_object(repo, tag.target_id(), pairs)?, None)?;
+                let tagger = tag.tagger().map_or_else(|| repo.signature(), |t| Ok(t.to_owned()))?;
+                let (message, _) = self.key.open_message(tag.message_bytes().unwrap_or_default())?;
+                repo.tag_annotation_create(tag.name().unwrap_or_default(), &target, &tagger, &String::from_utf8_lossy(&message))?
+            }
+            Some(ObjectType::Blob) => repo.blob(&self.key.open_blob(object.peel_to_blob()?.content())?)?,
+            _ => return Err(error(format!("Cannot decrypt object {}", id))),
+        };
+        self.remember(pairs, opened, id);
+        Ok(opened)
+    }
+
+    /// Translate `id` and every ancestor not translated yet, parents first. An
+    /// explicit stack, since history is far deeper than the call stack.
+    fn translate_commits(&self, repo: &Repository, id: Oid, pairs: &mut Vec<(Oid, Oid)>, seal: bool) -> Result<Oid> {
+        let done = |oid: &Oid| if seal { self.sealed.borrow().get(oid).copied() } else { self.opened.borrow().get(oid).copied() };
+        let mut stack = vec![id];
+        while let Some(&oid) = stack.last() {
+            if done(&oid).is_some() {
+                stack.pop();
+                continue;
+            }
+            let commit = repo.find_commit(oid)?;
+            let pending: Vec<Oid> = commit.parent_ids().filter(|parent| done(parent).is_none()).collect();
+            if !pending.is_empty() {
+                stack.extend(pending);
+                continue;
+            }
+
+            let parents = commit.parent_ids()
+                .map(|parent| repo.find_commit(done(&parent).expect("parents are translated first")))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            let (tree, message) = if seal {
+                let tree = self.translate_tree(repo, commit.tree_id(), pairs, true, self.key.encrypt_names)?;
+                (tree, self.key.seal_message(commit.message_bytes(), self.key.encrypt_names))
+            } else {
+                let (message, names) = self.key.open_message(commit.message_bytes())?;
+                (self.translate_tree(repo, commit.tree_id(), pairs, false, names)?, String::from_utf8_lossy(&message).into_owned())
+            };
+            let translated = repo.commit(
+                None,
+                &commit.author(),
+                &commit.committer(),
+                &message,
+                &repo.find_tree(tree)?,
+                &parents,
+            )?;
+            if seal {
+                self.remember(pairs, oid, translated);
+            } else {
+                self.remember(pairs, translated, oid);
+            }
+            stack.pop();
+        }
+        Ok(done(&id).expect("translated above"))
+    }
+
+    fn translate_tree(&self, repo: &Repository, id: Oid, pairs: &mut Vec<(Oid, Oid)>, seal: bool, names: bool) -> Result<Oid> {
+        let cached = if seal { self.sealed.borrow().get(&id).copied() } else { self.opened.borrow().get(&id).copied() };
+        if let Some(translated) = cached {
+            return Ok(translated);
+        }
+
+        let tree = repo.find_tree(id)?;
+        let mut builder = repo.treebuilder(None)?;
+        for entry in tree.iter() {
+            let name = match (names, seal) {
+                (false, _) => entry.name_bytes().to_vec(),
+                (true, true) => self.key.seal_name(entry.name_bytes()).into_bytes(),
+                (true, false) => self.key.open_name(entry.name().unwrap_or_default())?,
+            };
+            let target = match entry.kind() {
+                Some(ObjectType::Tree) => self.translate_tree(repo, entry.id(), pairs, seal, names)?,
+                Some(ObjectType::Blob) if seal => self.seal_object(repo, entry.id(), pairs)?,
+                Some(ObjectType::Blob) => self.open_object(repo, entry.id(), pairs)?,
+                // Submodule commits point outside this repository
+                _ => entry.id(),
+            };
+            builder.insert(name, target, entry.filemode())?;
+        }
+        let translated = builder.write()?;
+        if seal {
+            self.remember(pairs, id, translated);
+        } else {
+            self.remember(pairs, translated, id);
+        }
+        Ok(translated)
+    }
+}
+
+/// Read a passphrase from the terminal without echoing it
+pub fn prompt_passphrase(prompt: &str) -> Result<String> {
+    Ok(rpassword::prompt_password(prompt)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::tempdir;
+
+    fn key(names: bool) -> Key {
+        Key { master: [7; 32], encrypt_names: names, kdf: None }
+    }
+
+    #[test]
+    fn test_key_export_round_trip() {
+        let key = Key::from_passphrase("correct horse", true).unwrap();
+        let imported = Key::import(&key.export()).unwrap();
+        assert_eq!(imported.master, key.master);
+        assert!(imported.encrypt_names);
+        assert_eq!(imported.id(), key.id());
+
+        assert!(Key::import("ghs-key-AAAA").is_err());
+        assert!(Key::import("not a key").is_err());
+
+        // Keys exported before the derivation was recorded still import
+        let old = Key { master: [7; 32], encrypt_names: false, kdf: None };
+        assert!(Key::import(&old.export()).unwrap().kdf.is_none());
+    }
+
+    #[test]
+    fn test_passphrase_recovers_key_from_sealed_message() {
+        let key = Key::from_passphrase("correct horse", true).unwrap();
+        let message = key.seal_message(b"notes", true);
+        assert!(message.contains("kdf: argon2id m="));
+
+        let recovered = Key::from_sealed_message(message.as_bytes(), "correct horse").unwrap();
+        assert_eq!(recovered.master, key.master);
+        assert!(recovered.encrypt_names);
+        assert_eq!(recovered.open_message(message.as_bytes()).unwrap().0, b"notes");
+        assert!(Key::from_sealed_message(message.as_bytes(), "wrong horse").is_err());
+
+        let unrecorded = Key { master: [7; 32], encrypt_names: false, kdf: None }.seal_message(b"notes", false);
+        assert!(Key::from_sealed_message(unrecorded.as_bytes(), "correct horse").is_err());
+    }
+
+    #[test]
+    fn test_sealing_is_deterministic_and_keyed() {
+        let key = key(false);
+        let sealed = key.seal_blob(b"API_KEY=secret");
+        assert_eq!(sealed, key.seal_blob(b"API_KEY=secret"));
+        assert!(!sealed.windows(6).any(|w| w == b"secret"));
+        assert_eq!(key.open_blob(&sealed).unwrap(), b"API_KEY=secret");
+
+        let other = Key { master: [8; 32], encrypt_names: false, kdf: None };
+        assert!(other.open_blob(&sealed).is_err());
+        assert!(key.open_blob(b"API_KEY=secret").is_err());
+
+        let name = key.seal_name(b"passwords.txt");
+        assert!(!name.contains("passwords"));
+        assert_eq!(key.open_name(&name).unwrap(), b"passwords.txt");
+    }
+
+    #[test]
+    fn test_open_restores_the_sealed_commit() {
+        let dir = tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let signature = Signature::now("laptop", "laptop@example.com").unwrap();
+        let blob = repo.blob(b"secret notes").unwrap();
+        let mut sub = repo.treebuilder(None).unwrap();
+        sub.insert("plans.md", blob, 0o100644).unwrap();
+        let mut root = repo.treebuilder(None).unwrap();
+        root.insert("private", sub.write().unwrap(), 0o040000).unwrap();
+        let tree = repo.find_tree(root.write().unwrap()).unwrap();
+        let first = repo.commit(None, &signature, &signature, "renamed: a -> private/plans.md\n", &tree, &[]).unwrap();
+        let first = repo.find_commit(first).unwrap();
+        let second = repo.commit(None, &signature, &signature, "second\n", &tree, &[&first]).unwrap();
+
+        let sealing = Encryption::new(key(true), &repo.path().join("a"));
+        fs::create_dir_all(repo.path().join("a")).unwrap();
+        let sealed = sealing.seal(&repo, second).unwrap();
+        let sealed_commit = repo.find_commit(sealed).unwrap();
+        assert!(is_sealed(sealed_commit.message_bytes()));
+        assert!(sealed_commit.tree().unwrap().get_path(Path::new("private")).is_err());
+
+        // Another device, knowing nothing but the key, gets the same commits back
+        fs::create_dir_all(repo.path().join("b")).unwrap();
+        let opening = Encryption::new(key(true), &repo.path().join("b"));
+        assert_eq!(opening.open(&repo, sealed).unwrap(), second);
+        assert_eq!(opening.seal(&repo, second).unwrap(), sealed);
+
+        // Translations survive a restart
+        key(true).save(&repo.path().join("a")).unwrap();
+        let reloaded = Encryption::load(&repo.path().join("a")).unwrap().unwrap();
+        assert_eq!(reloaded.sealed.borrow().get(&second), Some(&sealed));
+        assert_eq!(reloaded.opened.borrow().len(), reloaded.sealed.borrow().len());
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use git2::{ObjectType, Oid};
+
+/// How long a checkout write is remembered. Long enough to outlast a slow poll interval.
+const ECHO_TTL: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expected {
+    /// Checkout wrote a file with this blob content
+    File(Oid),
+    /// Checkout deleted the path
+    Absent,
+    /// Checkout created or emptied a directory
+    Dir,
+}
+
+/// Paths written by the git engine during checkout, with the content written.
+///
+/// The watcher consults this before queueing a change: an event whose path still
+/// holds exactly what checkout put there is an echo of a remote change, not a local
+/// edit, and must not be synced back.
+#[derive(Debug, Default)]
+pub struct EchoFilter {
+    writes: Mutex<HashMap<PathBuf, (Expected, Instant)>>,
+}
+
+impl EchoFilter {
+    pub fn expect_file(&self, path: PathBuf, blob: Oid) {
+        self.expect(path, Expected::File(blob));
+    }
+
+    pub fn expect_absent(&self, path: PathBuf) {
+        self.expect(path, Expected::Absent);
+    }
+
+    pub fn expect_dir(&self, path: PathBuf) {
+        self.expect(path, Expected::Dir);
+    }
+
+    fn expect(&self, path: PathBuf, expected: Expected) {
+        let mut writes = self.writes.lock().unwrap();
+        writes.retain(|_, (_, at)| at.elapsed() < ECHO_TTL);
+        writes.insert(path, (expected, Instant::now()));
+    }
+
+    /// Whether `path` currently holds exactly what checkout wrote.
+    ///
+    /// A mismatch means the path was edited locally since, so it is forgotten and
+    /// later events for it are treated as local changes.
+    pub fn is_echo(&self, path: &Path) -> bool {
+        let mut writes = self.writes.lock().unwrap();
+        let Some(&(expected, at)) = writes.get(path) else {
+            return false;
+        };
+        if at.elapsed() >= ECHO_TTL {
+            writes.remove(path);
+            return false;
+        }
+
+        let matches = match expected {
+            Expected::File(blob) => {
+                path.is_file() && Oid::hash_file(ObjectType::Blob, path).ok() == Some(blob)
+            }
+            Expected::Absent => !path.exists(),
+            Expected::Dir => path.is_dir() || !path.exists(),
+        };
+        if !matches {
+            writes.remove(path);
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_unchanged_checkout_write_is_echo() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("a.txt");
+        fs::write(&path, "from remote").unwrap();
+
+        let filter = EchoFilter::default();
+        filter.expect_file(path.clone(), Oid::hash_object(ObjectType::Blob, b"from remote").unwrap());
+
+        // Several events for the same write are all suppressed
+        assert!(filter.is_echo(&path));
+        assert!(filter.is_echo(&path));
+    }
+
+    #[test]
+    fn test_local_edit_after_checkout_is_not_echo() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("a.txt");
+
+        let filter = EchoFilter::default();
+        filter.expect_file(path.clone(), Oid::hash_object(ObjectType::Blob, b"from remote").unwrap());
+        fs::write(&path, "edited locally").unwrap();
+
+        assert!(!filter.is_echo(&path));
+
+        // Forgotten once it diverged, even if the content goes back
+        fs::write(&path, "from remote").unwrap();
+        assert!(!filter.is_echo(&path));
+    }
+
+    #[test]
+    fn test_deletions_and_unknown_paths() {
+        let temp = tempdir().unwrap();
+        let filter = EchoFilter::default();
+
+        let deleted = temp.path().join("gone.txt");
+        filter.expect_absent(deleted.clone());
+        assert!(filter.is_echo(&deleted));
+
+        fs::write(&deleted, "recreated").unwrap();
+        assert!(!filter.is_echo(&deleted));
+
+        assert!(!filter.is_echo(&temp.path().join("other.txt")));
+    }
+}
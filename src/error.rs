@@ -1,5 +1,6 @@
 use thiserror::Error;
 use std::io;
+use crate::git::MassDeletion;
 
 pub type Result<T> = std::result::Result<T, GitHubSyncError>;
 
@@ -23,10 +24,28 @@ pub enum GitHubSyncError {
     #[error("GitHub error: {0}")]
     GitHubError(String),
 
+    #[error("Push held: {0}")]
+    MassDeletion(MassDeletion),
+
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
+    #[error("Daemon error: {0}")]
+    DaemonError(String),
+
+    #[error("Restore error: {0}")]
+    RestoreError(String),
+
     #[error("Unknown error: {0}")]
     UnknownError(String),
 }
 
+impl From<notify::Error> for GitHubSyncError {
+    fn from(err: notify::Error) -> Self {
+        GitHubSyncError::WatchError(err.to_string())
+    }
+}
+
 #[cfg(unix)]
 impl From<nix::Error> for GitHubSyncError {
     fn from(err: nix::Error) -> Self {
@@ -1,6 +1,6 @@
 use std::fs;
-use std::path::PathBuf;
 use octocrab::Octocrab;
+use octocrab::models::Repository;
 use crate::error::{Result, GitHubSyncError};
 use crate::logger;
 
@@ -29,7 +29,7 @@ impl GitHub {
             .map_err(|e| GitHubSyncError::GitHubError(e.to_string()))?;
 
         // Check if repo exists
-        match self.client.repos(user.login.clone()).get(name).await {
+        match self.client.repos(user.login.clone(), name).get().await {
             Ok(_) => {
                 logger::info(&format!("Repository {}/{} already exists", user.login, name));
                 Ok(format!("git@github.com:{}/{}.git", user.login, name))
@@ -37,15 +37,13 @@ impl GitHub {
             Err(_) => {
                 // Create new repository
                 logger::info(&format!("Creating new repository: {}", name));
-                let repo = self.client
-                    .repos()
-                    .create(name)
-                    .private(true)
-                    .send()
+                let body = serde_json::json!({ "name": name, "private": true });
+                let repo: Repository = self.client
+                    .post("/user/repos", Some(&body))
                     .await
                     .map_err(|e| GitHubSyncError::GitHubError(e.to_string()))?;
 
-                Ok(format!("git@github.com:{}/{}.git", user.login, name))
+                Ok(repo.ssh_url.unwrap_or_else(|| format!("git@github.com:{}/{}.git", user.login, name)))
             }
         }
     }
@@ -0,0 +1,319 @@
+// src/ipc.rs
+//
+// Control socket for the running daemon. The protocol is one JSON request per
+// line from the client, answered by one JSON response line.
+
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use tokio::sync::mpsc::UnboundedSender;
+use serde::{Serialize, Deserialize};
+use crate::config;
+use crate::error::{Result, GitHubSyncError};
+
+const SOCKET_FILE: &str = "ghs.sock";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    Status,
+    SyncNow,
+    /// Pull without committing or pushing local changes, like `ghs pull` without a daemon
+    Pull,
+    /// Keep collecting changes but stop syncing, indefinitely or for `duration_secs`
+    Pause {
+        #[serde(default)]
+        folder: Option<String>,
+        #[serde(default)]
+        duration_secs: Option<u64>,
+    },
+    Resume {
+        #[serde(default)]
+        folder: Option<String>,
+    },
+    ReloadConfig,
+    /// Tag the current state as a snapshot and sync it
+    Snapshot {
+        name: String,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// Roll `folder` back to a snapshot in a new commit and sync it
+    Rollback {
+        snapshot: String,
+        folder: String,
+    },
+    /// Compact old history by the configured policy
+    Gc {
+        #[serde(default)]
+        dry_run: bool,
+    },
+    /// Download the backups every device pushed
+    FetchBackups,
+    /// Delete this device's backups older than `older_than_secs`, or by the
+    /// configured retention
+    PruneBackups {
+        #[serde(default)]
+        older_than_secs: Option<u64>,
+    },
+    /// Push a mass deletion that is being held
+    Confirm {
+        #[serde(default)]
+        folder: Option<String>,
+    },
+    /// Stop the daemon; with `flush` pending commits are pushed first
+    Shutdown {
+        #[serde(default = "default_flush")]
+        flush: bool,
+    },
+}
+
+fn default_flush() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<serde_json::Value>,
+}
+
+impl Response {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self { ok: true, message: Some(message.into()), status: None }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { ok: false, message: Some(message.into()), status: None }
+    }
+
+    pub fn status(status: serde_json::Value) -> Self {
+        Self { ok: true, message: None, status: Some(status) }
+    }
+
+    /// The message of a successful response; a failed one is an error, so the
+    /// command exits non-zero
+    pub fn into_result(self) -> Result<String> {
+        let message = self.message.unwrap_or_default();
+        if self.ok {
+            Ok(message)
+        } else {
+            Err(GitHubSyncError::DaemonError(message))
+        }
+    }
+}
+
+/// A request handed to the watcher loop, which answers on `reply`
+pub struct ControlMessage {
+    pub request: Request,
+    pub reply: Sender<Response>,
+}
+
+pub fn socket_path() -> Result<PathBuf> {
+    Ok(config::state_dir()?.join(SOCKET_FILE))
+}
+
+/// Send `request` to the running daemon. Returns `None` when no daemon is listening.
+pub fn request(request: &Request) -> Result<Option<Response>> {
+    imp::request_at(&socket_path()?, request)
+}
+
+/// Listen on the control socket and forward requests to `handler` from a background thread.
+pub fn serve(handler: UnboundedSender<ControlMessage>) -> Result<()> {
+    imp::serve_at(socket_path()?, handler)
+}
+
+/// Remove the control socket when the daemon exits.
+pub fn cleanup() {
+    if let Ok(path) = socket_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::io::{BufRead, BufReader, ErrorKind, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::channel;
+    use std::thread;
+    use std::time::Duration;
+    use tokio::sync::mpsc::UnboundedSender;
+    use super::{ControlMessage, Request, Response};
+    use crate::error::{Result, GitHubSyncError};
+    use crate::logger;
+
+    /// Long enough for a sync over a slow connection
+    const REPLY_TIMEOUT: Duration = Duration::from_secs(300);
+    /// How long a client gets to send its request line and to take the reply
+    const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub fn request_at(path: &Path, request: &Request) -> Result<Option<Response>> {
+        let mut stream = match UnixStream::connect(path) {
+            Ok(stream) => stream,
+            Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => {
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+
+        let mut line = serde_json::to_string(request)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Failed to encode request: {}", e)))?;
+        line.push('\n');
+        stream.write_all(line.as_bytes())?;
+
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        let response = serde_json::from_str(&reply)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Invalid response from daemon: {}", e)))?;
+        Ok(Some(response))
+    }
+
+    pub fn serve_at(path: PathBuf, handler: UnboundedSender<ControlMessage>) -> Result<()> {
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(GitHubSyncError::UnknownError(format!(
+                    "Another instance is already listening on {}",
+                    path.display()
+                )));
+            }
+            // Left behind by a daemon that did not shut down cleanly
+            std::fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        // One thread per client, so a slow request or a silent client
+                        // never keeps `ghs status` or `ghs stop` waiting
+                        let handler = handler.clone();
+                        thread::spawn(move || {
+                            if let Err(e) = handle_client(stream, &handler) {
+                                logger::warn(&format!("Control request failed: {}", e));
+                            }
+                        });
+                    }
+                    Err(e) => logger::error(&format!("Control socket error: {}", e)),
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn handle_client(mut stream: UnixStream, handler: &UnboundedSender<ControlMessage>) -> Result<()> {
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let (reply, reply_rx) = channel();
+                if handler.send(ControlMessage { request, reply }).is_err() {
+                    Response::error("Daemon is shutting down")
+                } else {
+                    reply_rx
+                        .recv_timeout(REPLY_TIMEOUT)
+                        .unwrap_or_else(|_| Response::error("Daemon did not answer in time"))
+                }
+            }
+            Err(e) => Response::error(format!("Invalid request: {}", e)),
+        };
+
+        let mut out = serde_json::to_string(&response)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Failed to encode response: {}", e)))?;
+        out.push('\n');
+        stream.write_all(out.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::path::{Path, PathBuf};
+    use tokio::sync::mpsc::UnboundedSender;
+    use super::{ControlMessage, Request, Response};
+    use crate::error::Result;
+    use crate::logger;
+
+    pub fn request_at(_path: &Path, _request: &Request) -> Result<Option<Response>> {
+        Ok(None)
+    }
+
+    pub fn serve_at(_path: PathBuf, _handler: UnboundedSender<ControlMessage>) -> Result<()> {
+        logger::warn("Control socket is not supported on this platform");
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::thread;
+    use tokio::sync::mpsc::unbounded_channel;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_request_wire_format() {
+        assert_eq!(serde_json::to_string(&Request::SyncNow).unwrap(), r#"{"command":"sync-now"}"#);
+        assert_eq!(serde_json::to_string(&Request::Pull).unwrap(), r#"{"command":"pull"}"#);
+        let request: Request = serde_json::from_str(r#"{"command":"reload-config"}"#).unwrap();
+        assert_eq!(request, Request::ReloadConfig);
+
+        let request: Request = serde_json::from_str(r#"{"command":"pause","duration_secs":60}"#).unwrap();
+        assert_eq!(request, Request::Pause { folder: None, duration_secs: Some(60) });
+
+        let request: Request = serde_json::from_str(r#"{"command":"shutdown"}"#).unwrap();
+        assert_eq!(request, Request::Shutdown { flush: true });
+
+        let request: Request = serde_json::from_str(r#"{"command":"prune-backups"}"#).unwrap();
+        assert_eq!(request, Request::PruneBackups { older_than_secs: None });
+    }
+
+    #[test]
+    fn test_failed_response_is_an_error() {
+        assert_eq!(Response::ok("Sync completed").into_result().unwrap(), "Sync completed");
+        let error = Response::error("Sync failed: offline").into_result().unwrap_err();
+        assert!(error.to_string().contains("Sync failed: offline"));
+    }
+
+    #[test]
+    fn test_round_trip_through_socket() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test.sock");
+
+        let (tx, mut rx) = unbounded_channel::<ControlMessage>();
+        imp::serve_at(path.clone(), tx).unwrap();
+        thread::spawn(move || {
+            while let Some(msg) = rx.blocking_recv() {
+                let response = match msg.request {
+                    Request::Status => Response::status(serde_json::json!({ "offline": false })),
+                    other => Response::ok(format!("{:?}", other)),
+                };
+                msg.reply.send(response).unwrap();
+            }
+        });
+
+        // A client that connects and never sends a line holds up no one else
+        let _silent = std::os::unix::net::UnixStream::connect(&path).unwrap();
+
+        let response = imp::request_at(&path, &Request::Status).unwrap().unwrap();
+        assert!(response.ok);
+        assert_eq!(response.status.unwrap()["offline"], false);
+
+        let response = imp::request_at(&path, &Request::ReloadConfig).unwrap().unwrap();
+        assert_eq!(response.message.as_deref(), Some("ReloadConfig"));
+    }
+
+    #[test]
+    fn test_no_daemon_is_not_an_error() {
+        let temp = tempdir().unwrap();
+        let response = imp::request_at(&temp.path().join("missing.sock"), &Request::Status).unwrap();
+        assert!(response.is_none());
+    }
+}
@@ -0,0 +1,197 @@
+// src/lock.rs
+//
+// Advisory locks that keep GitHub Sync processes from stepping on each other. The
+// kernel drops a flock when its holder exits, however it exits, so a lock can never
+// go stale the way a PID file does.
+
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use crate::config;
+use crate::error::{Result, GitHubSyncError};
+
+const INSTANCE_LOCK: &str = "ghs.lock";
+const FOLDER_LOCK_DIR: &str = "locks";
+
+/// An exclusive lock held until the value is dropped.
+#[derive(Debug)]
+pub struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// The daemon-wide lock; only one `ghs start` can hold it at a time.
+    pub fn instance() -> Result<Option<Self>> {
+        Self::try_acquire(config::state_dir()?.join(INSTANCE_LOCK))
+    }
+
+    /// The lock for git operations on the repository at `folder`.
+    ///
+    /// The daemon holds it for its whole lifetime; one-shot commands like `ghs push`
+    /// take it around their own git work.
+    pub fn folder(folder: &Path) -> Result<Option<Self>> {
+        let dir = config::state_dir()?.join(FOLDER_LOCK_DIR);
+        fs::create_dir_all(&dir)?;
+        Self::try_acquire(dir.join(folder_lock_name(folder)))
+    }
+
+    /// Like [`FileLock::folder`], but busy is an error naming the folder.
+    pub fn folder_or_busy(folder: &Path) -> Result<Self> {
+        Self::folder(folder)?.ok_or_else(|| {
+            GitHubSyncError::UnknownError(format!(
+                "{} is in use by another GitHub Sync process; try again once it has finished",
+                folder.display()
+            ))
+        })
+    }
+
+    /// Take the lock at `path` without blocking. Returns `None` if another process holds it.
+    pub fn try_acquire(path: PathBuf) -> Result<Option<Self>> {
+        let file = OpenOptions::new().create(true).truncate(false).write(true).open(&path)?;
+        if !imp::try_lock(&file)? {
+            return Ok(None);
+        }
+        Ok(Some(Self { _file: file }))
+    }
+}
+
+/// Lock file name for a folder: its name for readability, plus a hash of its
+/// canonical path so that distinct folders never share a lock.
+fn folder_lock_name(folder: &Path) -> String {
+    let folder = folder.canonicalize().unwrap_or_else(|_| folder.to_path_buf());
+    let hash = Sha256::digest(folder.as_os_str().as_encoded_bytes());
+    let hash: String = hash[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    let name: String = folder
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    format!("{}-{}.lock", name.trim_start_matches('.'), hash)
+}
+
+/// Whether `pid` is a live GitHub Sync process rather than a dead one or an
+/// unrelated process that was given the same PID after a crash.
+pub fn is_running_instance(pid: u32) -> bool {
+    imp::is_alive(pid) && imp::same_executable(pid)
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::fs::File;
+    use std::os::unix::io::AsRawFd;
+    use nix::errno::Errno;
+    use nix::fcntl::{flock, FlockArg};
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+    use crate::error::Result;
+
+    pub fn try_lock(file: &File) -> Result<bool> {
+        match flock(file.as_raw_fd(), FlockArg::LockExclusiveNonblock) {
+            Ok(()) => Ok(true),
+            Err(Errno::EWOULDBLOCK) => Ok(false),
+            Err(e) => Err(std::io::Error::from(e).into()),
+        }
+    }
+
+    pub fn is_alive(pid: u32) -> bool {
+        let Ok(pid) = i32::try_from(pid) else {
+            return false;
+        };
+        // EPERM means the process exists but belongs to someone else
+        pid > 0 && matches!(kill(Pid::from_raw(pid), None), Ok(()) | Err(Errno::EPERM))
+    }
+
+    /// Compare executables by file name, which survives the binary being upgraded
+    /// in place (the kernel then reports it as "ghs (deleted)"). Without procfs, or
+    /// without permission to look, the process is not taken for ours: `ghs stop`
+    /// must never signal a stranger, and the instance lock still keeps two daemons
+    /// from running at once.
+    pub fn same_executable(pid: u32) -> bool {
+        let Ok(theirs) = std::fs::read_link(format!("/proc/{}/exe", pid)) else {
+            return false;
+        };
+        let Ok(ours) = std::env::current_exe() else {
+            return false;
+        };
+        let name = |path: &std::path::Path| {
+            path.file_name()
+                .map(|n| n.to_string_lossy().trim_end_matches(" (deleted)").to_string())
+        };
+        name(&theirs) == name(&ours)
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::fs::File;
+    use crate::error::Result;
+
+    pub fn try_lock(_file: &File) -> Result<bool> {
+        Ok(true)
+    }
+
+    pub fn is_alive(_pid: u32) -> bool {
+        true
+    }
+
+    pub fn same_executable(_pid: u32) -> bool {
+        true
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lock_is_exclusive_until_dropped() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test.lock");
+
+        let held = FileLock::try_acquire(path.clone()).unwrap();
+        assert!(held.is_some());
+        assert!(FileLock::try_acquire(path.clone()).unwrap().is_none());
+
+        drop(held);
+        assert!(FileLock::try_acquire(path).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_running_instance_detection() {
+        assert!(is_running_instance(std::process::id()));
+
+        // A child that has exited and been reaped no longer exists
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!is_running_instance(pid));
+    }
+
+    #[test]
+    fn test_unrelated_process_is_not_an_instance() {
+        let mut child = std::process::Command::new("sleep").arg("5").spawn().unwrap();
+        let pid = child.id();
+        let running = is_running_instance(pid);
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(!running);
+    }
+
+    #[test]
+    fn test_folder_lock_names() {
+        let name = folder_lock_name(Path::new("/nonexistent/home/.github-sync"));
+        assert!(name.starts_with("github-sync-") && name.ends_with(".lock"), "{}", name);
+        assert_ne!(folder_lock_name(Path::new("/nonexistent/a")), folder_lock_name(Path::new("/nonexistent/b")));
+        // Paths that only differ in characters the readable part replaces
+        assert_ne!(folder_lock_name(Path::new("/nonexistent/a b")), folder_lock_name(Path::new("/nonexistent/a_b")));
+        assert_ne!(folder_lock_name(Path::new("/nonexistent/a/b")), folder_lock_name(Path::new("/nonexistent/a_b")));
+
+        // Every spelling of the same folder shares one lock
+        let temp = tempdir().unwrap();
+        fs::create_dir(temp.path().join("notes")).unwrap();
+        assert_eq!(folder_lock_name(&temp.path().join("notes")), folder_lock_name(&temp.path().join("notes/../notes")));
+    }
+}
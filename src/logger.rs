@@ -1,7 +1,19 @@
 // src/logger.rs
 
+use std::fs::{self, File, OpenOptions};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 use colored::*;
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Serialize, Deserialize};
+
+/// Rotate the log file once it grows past this size
+const MAX_LOG_SIZE: u64 = 10 * 1024 * 1024;
+/// Rotated files kept next to the current one (`ghs.log.1` is the newest)
+const MAX_LOG_FILES: usize = 5;
+pub const LOG_FILE: &str = "ghs.log";
 
 #[derive(Debug)]
 pub enum Status {
@@ -11,21 +23,220 @@ pub enum Status {
     Warn,     // For warnings
     Sync,     // For sync-specific operations
     Watch,    // For file watcher events
+    Debug,    // For details only shown with --verbose
+}
+
+/// Verbosity, from least to most output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Status {
+    fn level(&self) -> Level {
+        match self {
+            Status::Error => Level::Error,
+            Status::Warn => Level::Warn,
+            Status::Success | Status::Info | Status::Sync | Status::Watch => Level::Info,
+            Status::Debug => Level::Debug,
+        }
+    }
+
+    /// Event type in JSON output
+    fn event(&self) -> &'static str {
+        match self {
+            Status::Success => "success",
+            Status::Error => "error",
+            Status::Info => "info",
+            Status::Warn => "warn",
+            Status::Sync => "sync",
+            Status::Watch => "watch",
+            Status::Debug => "debug",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self.level() {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        }
+    }
+}
+
+/// Line format for the console and the log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Format {
+    /// Colored, human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, for log pipelines
+    Json,
+}
+
+/// Structured details attached to a line. Text output shows only the message;
+/// JSON output includes every field that is set.
+#[derive(Debug, Default)]
+pub struct Fields {
+    path: Option<PathBuf>,
+    commit: Option<String>,
+    duration: Option<Duration>,
+    error: Vec<String>,
+}
+
+impl Fields {
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn commit(mut self, commit: impl ToString) -> Self {
+        self.commit = Some(commit.to_string());
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// The error and each of its sources, outermost first
+    pub fn error(mut self, error: &dyn std::error::Error) -> Self {
+        let mut source = Some(error);
+        while let Some(e) = source {
+            self.error.push(e.to_string());
+            source = e.source();
+        }
+        self
+    }
+}
+
+struct Logger {
+    level: Level,
+    format: Format,
+    /// Set by `--verbose`/`--quiet`, which win over the `log_level` config
+    level_from_cli: bool,
+    folder: Option<String>,
+    file: Option<LogFile>,
+}
+
+static LOGGER: Mutex<Logger> = Mutex::new(Logger {
+    level: Level::Info,
+    format: Format::Text,
+    level_from_cli: false,
+    folder: None,
+    file: None,
+});
+
+fn logger() -> std::sync::MutexGuard<'static, Logger> {
+    // A panic while logging must not silence every later message
+    LOGGER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Pick the verbosity: the command line first, then the config, then `info`.
+pub fn init_level(cli: Option<Level>, config: Option<Level>) {
+    let mut logger = logger();
+    logger.level_from_cli = cli.is_some();
+    logger.level = cli.or(config).unwrap_or(Level::Info);
+}
+
+/// Choose the line format. Colors are dropped when stdout is not a terminal.
+pub fn init_format(format: Format) {
+    logger().format = format;
+    if !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+}
+
+/// Apply a reloaded `log_level`, unless the command line set one.
+pub fn reload_level(config: Option<Level>) {
+    let mut logger = logger();
+    if !logger.level_from_cli {
+        logger.level = config.unwrap_or(Level::Info);
+    }
+}
+
+/// Name of the folder the daemon syncs, included in every line from now on.
+pub fn set_folder(folder: &str) {
+    logger().folder = Some(folder.to_string());
+}
+
+/// Also append every line to `ghs.log` in `dir`, rotating it by size and by day.
+pub fn log_to_file(dir: &Path) -> std::io::Result<PathBuf> {
+    let path = dir.join(LOG_FILE);
+    logger().file = Some(LogFile::open(path.clone(), MAX_LOG_SIZE, MAX_LOG_FILES)?);
+    Ok(path)
 }
 
 pub fn log(status: Status, message: &str) {
-    let timestamp = Local::now().format("%H:%M:%S").to_string().dimmed();
-    
-    let status_str = match status {
-        Status::Success => "✓".green().bold(),
-        Status::Error => "✗".red().bold(),
-        Status::Info => "ℹ".bright_blue().bold(),
-        Status::Warn => "!".yellow().bold(),
-        Status::Sync => "⟳".magenta().bold(),
-        Status::Watch => "👁".bright_cyan().bold(),
+    log_with(status, message, Fields::default());
+}
+
+pub fn log_with(status: Status, message: &str, fields: Fields) {
+    let mut logger = logger();
+    if status.level() > logger.level {
+        return;
+    }
+
+    let now = Local::now();
+    let folder = logger.folder.as_deref().map(|f| format!("[{}] ", f)).unwrap_or_default();
+
+    let (console, file_line) = match logger.format {
+        Format::Text => {
+            let timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string().dimmed();
+            let status_str = match status {
+                Status::Success => "✓".green().bold(),
+                Status::Error => "✗".red().bold(),
+                Status::Info => "ℹ".bright_blue().bold(),
+                Status::Warn => "!".yellow().bold(),
+                Status::Sync => "⟳".magenta().bold(),
+                Status::Watch => "👁".bright_cyan().bold(),
+                Status::Debug => "·".dimmed(),
+            };
+            let line = format!("{} {:<5} {}{}", now.format("%Y-%m-%d %H:%M:%S"), status.label(), folder, message);
+            (format!("{} {} {}{}", timestamp, status_str, folder, message), line)
+        }
+        Format::Json => {
+            let line = json_line(now, &status, logger.folder.as_deref(), message, &fields);
+            (line.clone(), line)
+        }
     };
 
-    println!("{} {} {}", timestamp, status_str, message);
+    println!("{}", console);
+
+    if let Some(file) = logger.file.as_mut() {
+        // Nowhere better to report a failing log file; the console still has the line
+        let _ = file.write(now, &format!("{}\n", file_line));
+    }
+}
+
+fn json_line(now: DateTime<Local>, status: &Status, folder: Option<&str>, message: &str, fields: &Fields) -> String {
+    let mut entry = serde_json::Map::new();
+    entry.insert("timestamp".into(), now.to_rfc3339().into());
+    entry.insert("level".into(), status.label().to_lowercase().into());
+    entry.insert("event".into(), status.event().into());
+    if let Some(folder) = folder {
+        entry.insert("folder".into(), folder.into());
+    }
+    entry.insert("message".into(), message.into());
+    if let Some(path) = &fields.path {
+        entry.insert("path".into(), path.to_string_lossy().into());
+    }
+    if let Some(commit) = &fields.commit {
+        entry.insert("commit".into(), commit.as_str().into());
+    }
+    if let Some(duration) = fields.duration {
+        entry.insert("duration_ms".into(), (duration.as_millis() as u64).into());
+    }
+    if !fields.error.is_empty() {
+        entry.insert("error".into(), fields.error.clone().into());
+    }
+    serde_json::Value::Object(entry).to_string()
 }
 
 // Convenience functions
@@ -35,10 +246,67 @@ pub fn info(msg: &str) { log(Status::Info, msg); }
 pub fn warn(msg: &str) { log(Status::Warn, msg); }
 pub fn sync(msg: &str) { log(Status::Sync, msg); }
 pub fn watch(msg: &str) { log(Status::Watch, msg); }
+pub fn debug(msg: &str) { log(Status::Debug, msg); }
+
+/// Append-only log file rotated to `<name>.1`, `<name>.2`, ... when it exceeds
+/// `max_size` or when the first line of a new day is written.
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    day: NaiveDate,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl LogFile {
+    fn open(path: PathBuf, max_size: u64, max_files: usize) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let meta = file.metadata()?;
+        let day = meta.modified()
+            .map(|t| DateTime::<Local>::from(t).date_naive())
+            .unwrap_or_else(|_| Local::now().date_naive());
+        Ok(Self { path, file, size: meta.len(), day, max_size, max_files })
+    }
+
+    fn write(&mut self, now: DateTime<Local>, line: &str) -> std::io::Result<()> {
+        let today = now.date_naive();
+        if self.size > 0 && (self.size + line.len() as u64 > self.max_size || today != self.day) {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        self.day = today;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let rotated = |n: usize| PathBuf::from(format!("{}.{}", self.path.display(), n));
+
+        let _ = fs::remove_file(rotated(self.max_files));
+        for n in (1..self.max_files).rev() {
+            let from = rotated(n);
+            if from.exists() {
+                fs::rename(&from, rotated(n + 1))?;
+            }
+        }
+        if self.max_files > 0 {
+            fs::rename(&self.path, rotated(1))?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
+    use tempfile::tempdir;
 
     #[test]
     fn test_all_log_types() {
@@ -49,5 +317,80 @@ mod tests {
         warn("Large number of files changed");
         sync("Pulling changes from remote");
         watch("File modified: src/main.rs");
+        debug("Ignoring own checkout write");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_levels() {
+        assert!(Status::Error.level() < Level::Warn);
+        assert!(Status::Watch.level() <= Level::Info);
+        assert!(Status::Debug.level() > Level::Info);
+        assert_eq!(serde_json::from_str::<Level>("\"debug\"").unwrap(), Level::Debug);
+    }
+
+    #[test]
+    fn test_json_line() {
+        let now = Local.with_ymd_and_hms(2026, 10, 19, 14, 3, 22).unwrap();
+        let error = std::io::Error::other("connection refused");
+        let fields = Fields::default()
+            .path("/home/me/.github-sync/notes/a.txt")
+            .commit("0123abcd")
+            .duration(Duration::from_millis(1500))
+            .error(&crate::error::GitHubSyncError::from(error));
+
+        let line = json_line(now, &Status::Sync, Some("notes"), "Sync failed", &fields);
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(json["timestamp"], now.to_rfc3339());
+        assert_eq!(json["level"], "info");
+        assert_eq!(json["event"], "sync");
+        assert_eq!(json["folder"], "notes");
+        assert_eq!(json["path"], "/home/me/.github-sync/notes/a.txt");
+        assert_eq!(json["commit"], "0123abcd");
+        assert_eq!(json["duration_ms"], 1500);
+        assert_eq!(json["error"], serde_json::json!(["IO error: connection refused", "connection refused"]));
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn test_json_line_omits_unset_fields() {
+        let line = json_line(Local::now(), &Status::Warn, None, "careful", &Fields::default());
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["level"], "warn");
+        assert!(json.get("folder").is_none());
+        assert!(json.get("error").is_none());
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_keeps_max_files() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test.log");
+        let mut log = LogFile::open(path.clone(), 20, 2).unwrap();
+        let now = Local::now();
+
+        for i in 0..4 {
+            log.write(now, &format!("line {} of the log\n", i)).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line 3 of the log\n");
+        assert_eq!(fs::read_to_string(temp.path().join("test.log.1")).unwrap(), "line 2 of the log\n");
+        assert_eq!(fs::read_to_string(temp.path().join("test.log.2")).unwrap(), "line 1 of the log\n");
+        assert!(!temp.path().join("test.log.3").exists());
+    }
+
+    #[test]
+    fn test_rotates_on_new_day() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test.log");
+        let mut log = LogFile::open(path.clone(), MAX_LOG_SIZE, 3).unwrap();
+
+        let monday = Local.with_ymd_and_hms(2026, 10, 19, 23, 59, 0).unwrap();
+        let tuesday = Local.with_ymd_and_hms(2026, 10, 20, 0, 1, 0).unwrap();
+        log.write(monday, "late\n").unwrap();
+        log.write(monday, "later\n").unwrap();
+        log.write(tuesday, "early\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "early\n");
+        assert_eq!(fs::read_to_string(temp.path().join("test.log.1")).unwrap(), "late\nlater\n");
+    }
+}
@@ -1,14 +1,26 @@
 use clap::{Parser, Subcommand};
-use colored::*;
 
+mod changes;
 mod commands;
 mod config;
+mod crypto;
+mod echo;
 mod error;
 mod git;
 mod github;
+mod ipc;
+mod lock;
 mod logger;
 mod watcher;
+mod worker;
 mod remote_watcher;
+mod secrets;
+mod status;
+mod systemd;
+mod trash;
+mod util;
+#[cfg(test)]
+mod test_support;
 
 #[derive(Parser)]
 #[command(name = "ghs")]
@@ -16,6 +28,18 @@ mod remote_watcher;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Also show debug output
+    #[arg(short, long, global = true, conflicts_with = "quiet")]
+    verbose: bool,
+
+    /// Only show warnings and errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Line format for console and log file output
+    #[arg(long, global = true, value_enum, default_value_t)]
+    log_format: logger::Format,
 }
 
 #[derive(Subcommand)]
@@ -25,16 +49,40 @@ enum Commands {
         /// Name of the repository to sync
         #[arg(short, long)]
         name: String,
+
+        /// Detach from the terminal and run in the background, logging to the state directory
+        #[arg(short, long, conflicts_with = "foreground")]
+        daemon: bool,
+
+        /// Stay in the foreground (the default); for service managers such as systemd
+        #[arg(long)]
+        foreground: bool,
     },
     
     /// Stop the GitHub Sync service
-    Stop,
+    Stop {
+        /// Commit pending changes but skip pushing them before exiting
+        #[arg(long)]
+        no_flush: bool,
+    },
     
     /// Show current status
-    Status,
+    Status {
+        /// Print machine-readable JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
     
     /// Configure GitHub Sync
     Config {
+        /// SSH URL of the remote repository
+        #[arg(short, long)]
+        remote: Option<String>,
+
+        /// Comma-separated paths to watch
+        #[arg(short, long)]
+        paths: Option<String>,
+
         /// Branch to sync with
         #[arg(short, long)]
         branch: Option<String>,
@@ -57,26 +105,341 @@ enum Commands {
     Pull,
 
     /// Show sync history
-    Logs,
+    Logs {
+        /// Number of commits to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+
+        /// Only commits after this date, RFC 3339 time or duration ago (e.g. 2026-10-01, 7d)
+        #[arg(long, value_parser = commands::logs::parse_time)]
+        since: Option<std::time::SystemTime>,
+
+        /// Only commits before this date, RFC 3339 time or duration ago
+        #[arg(long, value_parser = commands::logs::parse_time)]
+        until: Option<std::time::SystemTime>,
+
+        /// Only commits touching this file or directory
+        #[arg(short, long)]
+        path: Option<std::path::PathBuf>,
+
+        /// Only commits made by this device (its git user.name)
+        #[arg(short, long)]
+        device: Option<String>,
+
+        /// Only commits touching this synced folder
+        #[arg(short, long)]
+        folder: Option<String>,
+
+        /// Print machine-readable JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Restore a file or folder, including deleted ones, from sync history
+    Restore {
+        /// File or folder to restore, relative to the sync root or the current directory inside it
+        path: std::path::PathBuf,
+
+        /// Restore it as it was at this date, RFC 3339 time or duration ago (e.g. 2026-10-01, 3d)
+        #[arg(long, value_parser = commands::logs::parse_time, conflicts_with_all = ["commit", "snapshot"])]
+        at: Option<std::time::SystemTime>,
+
+        /// Restore it from this commit (see 'ghs history')
+        #[arg(long, conflicts_with = "snapshot")]
+        commit: Option<String>,
+
+        /// Restore it as it was in this snapshot (see 'ghs snapshot list')
+        #[arg(long)]
+        snapshot: Option<String>,
+
+        /// Write the restored copy here instead of over the original
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// List the versions of a file or folder with their times and devices
+    History {
+        /// File or folder, relative to the sync root or the current directory inside it
+        path: std::path::PathBuf,
+    },
+
+    /// Pause syncing; changes keep being collected and sync on resume
+    Pause {
+        /// Folder to pause (defaults to the running one)
+        #[arg(short, long)]
+        folder: Option<String>,
+
+        /// Resume automatically after this long, e.g. 30m, 2h, 1h30m
+        #[arg(long = "for", value_parser = util::parse_duration)]
+        duration: Option<std::time::Duration>,
+    },
+
+    /// Resume syncing after `ghs pause`
+    Resume {
+        /// Folder to resume (defaults to the running one)
+        #[arg(short, long)]
+        folder: Option<String>,
+    },
+
+    /// Push deletions held back because they remove many files at once
+    Confirm {
+        /// Folder whose deletions to push (defaults to the running one)
+        #[arg(short, long)]
+        folder: Option<String>,
+    },
+
+    /// Manage the systemd service
+    Service {
+        #[command(subcommand)]
+        action: ServiceAction,
+    },
+
+    /// Squash old history by the configured policy and shrink the repository
+    Gc {
+        /// Only report how many commits would remain
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Name points in the sync history and roll folders back to them
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+
+    /// Inspect, restore and prune the backups pulls make of replaced local history
+    Backups {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+
+    /// Encrypt everything pushed with a key derived from a passphrase
+    Encrypt {
+        #[command(subcommand)]
+        action: EncryptAction,
+    },
+
+    /// Manage files that pulls deleted because another device removed them
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnapshotAction {
+    /// Tag the current state of the sync repository; it is pushed with the next sync
+    Create {
+        /// Snapshot name, e.g. before-migration
+        name: String,
+
+        /// Describe what the snapshot is for
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+
+    /// List snapshots, newest first
+    List {
+        /// Print machine-readable JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Make a folder match a snapshot again, committed and synced as a new change
+    Rollback {
+        /// Snapshot to roll back to
+        name: String,
+
+        /// Synced folder to roll back
+        #[arg(short, long)]
+        folder: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupAction {
+    /// List backups, newest first
+    List {
+        /// Also fetch the backups other devices pushed
+        #[arg(long)]
+        remote: bool,
+
+        /// Print machine-readable JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the commits and files a backup has that the current history lacks
+    Show {
+        /// Backup name, as listed by 'ghs backups list'
+        name: String,
+
+        /// Print machine-readable JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Write files from a backup into the sync folder, synced as a new change
+    Restore {
+        /// Backup name, as listed by 'ghs backups list'
+        name: String,
+
+        /// Only this file or directory, relative to the sync root
+        path: Option<std::path::PathBuf>,
+    },
+
+    /// Delete this device's backups by the configured retention, or by age
+    Prune {
+        /// Delete every backup made longer ago than this, e.g. 7d
+        #[arg(long, value_parser = util::parse_duration)]
+        older_than: Option<std::time::Duration>,
+    },
+}
+
+#[derive(Subcommand)]
+enum EncryptAction {
+    /// Derive a key, then replace the remote history with an encrypted copy
+    Init {
+        /// Encrypt file and directory names too
+        #[arg(long)]
+        names: bool,
+    },
+
+    /// Print the key, to import on another device
+    Export,
+
+    /// Use a key exported on another device, or its passphrase
+    Import {
+        /// The exported key or the passphrase; prompted for when omitted
+        key: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TrashAction {
+    /// List files in the trash, newest first
+    List {
+        /// Print machine-readable JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Put a file back where it was
+    Restore {
+        /// Trash id, or the original path relative to the sync root (newest match wins)
+        item: String,
+
+        /// Write the file here instead of its original location
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Permanently delete files from the trash
+    Empty {
+        /// Only files trashed longer ago than this, e.g. 7d
+        #[arg(long, value_parser = util::parse_duration)]
+        older_than: Option<std::time::Duration>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ServiceAction {
+    /// Generate, enable and start a systemd unit for the current configuration
+    Install {
+        /// Install as a user service (the only kind currently supported)
+        #[arg(long)]
+        user: bool,
+
+        /// Name of the repository to sync
+        #[arg(short, long)]
+        name: String,
+    },
+
+    /// Stop, disable and remove the systemd unit
+    Uninstall {
+        /// Name of the repository whose unit to remove
+        #[arg(short, long)]
+        name: String,
+    },
+
+    /// Show whether the service is installed and running
+    Status {
+        /// Name of the repository; every installed unit if omitted
+        #[arg(short, long)]
+        name: Option<String>,
+    },
 }
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
 
+    let cli_level = if cli.verbose {
+        Some(logger::Level::Debug)
+    } else if cli.quiet {
+        Some(logger::Level::Warn)
+    } else {
+        None
+    };
+    logger::init_level(cli_level, config::Config::load().ok().and_then(|c| c.log_level));
+    logger::init_format(cli.log_format);
+
     let result = match cli.command {
-        Commands::Start { name } => commands::start::handle(name).await,
-        Commands::Stop => commands::stop::handle(),
-        Commands::Status => commands::status::handle(),
-        Commands::Config { branch, interval } => {
-            commands::config::handle(branch, interval)
+        Commands::Start { name, daemon, foreground: _ } => {
+            if daemon {
+                commands::start::spawn_daemon(&name, cli_level, cli.log_format).await
+            } else {
+                commands::start::handle(name).await
+            }
+        }
+        Commands::Stop { no_flush } => commands::stop::handle(no_flush),
+        Commands::Status { json } => commands::status::handle(json),
+        Commands::Config { remote, paths, branch, interval } => {
+            commands::config::handle(remote, branch, paths, interval)
         }
         Commands::Auth { token } => {
             github::GitHub::save_token(&token)
         }
         Commands::Push => commands::push::handle(),
         Commands::Pull => commands::pull::handle(),
-        Commands::Logs => commands::logs::handle(),
+        Commands::Logs { count, since, until, path, device, folder, json } => {
+            commands::logs::handle(commands::logs::Options { count, since, until, path, device, folder, json })
+        }
+        Commands::Restore { path, at, commit, snapshot, output } => {
+            let commit = commit.or_else(|| snapshot.map(|name| git::snapshot_ref(&name)));
+            commands::restore::restore(path, at, commit, output)
+        }
+        Commands::History { path } => commands::restore::history(path),
+        Commands::Pause { folder, duration } => commands::pause::pause(folder, duration),
+        Commands::Resume { folder } => commands::pause::resume(folder),
+        Commands::Confirm { folder } => commands::confirm::handle(folder),
+        Commands::Service { action } => match action {
+            ServiceAction::Install { user, name } => commands::service::install(user, &name),
+            ServiceAction::Uninstall { name } => commands::service::uninstall(&name),
+            ServiceAction::Status { name } => commands::service::status(name.as_deref()),
+        },
+        Commands::Gc { dry_run } => commands::gc::handle(dry_run),
+        Commands::Snapshot { action } => match action {
+            SnapshotAction::Create { name, message } => commands::snapshot::create(name, message),
+            SnapshotAction::List { json } => commands::snapshot::list(json),
+            SnapshotAction::Rollback { name, folder } => commands::snapshot::rollback(name, folder),
+        },
+        Commands::Backups { action } => match action {
+            BackupAction::List { remote, json } => commands::backups::list(remote, json),
+            BackupAction::Show { name, json } => commands::backups::show(&name, json),
+            BackupAction::Restore { name, path } => commands::backups::restore(&name, path),
+            BackupAction::Prune { older_than } => commands::backups::prune(older_than),
+        },
+        Commands::Encrypt { action } => match action {
+            EncryptAction::Init { names } => commands::encrypt::init(names),
+            EncryptAction::Export => commands::encrypt::export(),
+            EncryptAction::Import { key } => commands::encrypt::import(key),
+        },
+        Commands::Trash { action } => match action {
+            TrashAction::List { json } => commands::trash::list(json),
+            TrashAction::Restore { item, output } => commands::trash::restore(&item, output),
+            TrashAction::Empty { older_than } => commands::trash::empty(older_than),
+        },
     };
 
     if let Err(e) = result {
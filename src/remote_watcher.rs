@@ -0,0 +1,37 @@
+// src/remote_watcher.rs
+//
+// Notices what other devices push. Every `sync_interval` the sync worker lists
+// the remote's references, which downloads nothing, and the watcher loop is told
+// to sync once the remote branch has moved.
+
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::time::{self, MissedTickBehavior};
+use crate::logger;
+use crate::worker::SyncWorker;
+
+/// Check the remote through `worker` every `interval`; a message arrives on the
+/// returned channel whenever it has changes to pull. Stops once that is dropped.
+pub fn start(worker: SyncWorker, interval: Duration) -> UnboundedReceiver<()> {
+    let (tx, rx) = unbounded_channel();
+    tokio::spawn(async move {
+        let mut ticks = time::interval(interval.max(Duration::from_secs(1)));
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick is immediate, and startup has just synced
+        ticks.tick().await;
+
+        while !tx.is_closed() {
+            ticks.tick().await;
+            match worker.call(|git| git.remote_changed()).await {
+                Ok(true) => {
+                    logger::debug("The remote has new changes");
+                    let _ = tx.send(());
+                }
+                Ok(false) => {}
+                // The sync itself reports an unreachable remote
+                Err(e) => logger::debug(&format!("Checking the remote failed: {}", e)),
+            }
+        }
+    });
+    rx
+}
@@ -0,0 +1,235 @@
+// src/status.rs
+//
+// The status model shared by the daemon, which produces it, and `ghs status`,
+// which prints it as text or JSON. The JSON form is a public interface for
+// dashboards and shell prompts: add fields, but do not rename or remove them.
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+use serde::{Serialize, Deserialize};
+use crate::config;
+use crate::error::Result;
+use crate::git::{GitSync, MassDeletion};
+use crate::secrets::SecretFinding;
+
+const STATUS_FILE: &str = "status.json";
+
+/// Where the running daemon writes its status, in the state directory rather than
+/// wherever it was started from
+pub fn status_file() -> Result<PathBuf> {
+    Ok(config::state_dir()?.join(STATUS_FILE))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncState {
+    /// Everything is committed and pushed
+    Idle,
+    /// Local changes or commits are waiting for the next sync
+    Pending,
+    /// A pull and push, or another operation on the repository, is running
+    Syncing,
+    /// The remote is unreachable; commits are queued and retried
+    Offline,
+    /// Syncing was paused with `ghs pause`
+    Paused,
+    /// Unpushed commits delete many files; nothing syncs until `ghs confirm`
+    Held,
+    /// No daemon is running; the rest is what the last one reported
+    Stopped,
+}
+
+impl SyncState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncState::Idle => "idle",
+            SyncState::Pending => "pending",
+            SyncState::Syncing => "syncing",
+            SyncState::Offline => "offline",
+            SyncState::Paused => "paused",
+            SyncState::Held => "held",
+            SyncState::Stopped => "stopped",
+        }
+    }
+}
+
+/// Status of one synced folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderStatus {
+    pub name: String,
+    pub path: PathBuf,
+    pub state: SyncState,
+    /// Last successful sync, if any since the daemon started
+    #[serde(default, with = "crate::util::rfc3339::option")]
+    pub last_sync: Option<SystemTime>,
+    pub last_error: Option<String>,
+    #[serde(default, with = "crate::util::rfc3339::option")]
+    pub next_retry: Option<SystemTime>,
+    /// Changed paths not committed yet
+    #[serde(default)]
+    pub pending_paths: Vec<String>,
+    /// Local commits not pushed yet
+    #[serde(default)]
+    pub queued_commits: usize,
+    /// Commits on the local branch that the remote-tracking branch lacks, and vice versa
+    pub ahead: Option<usize>,
+    pub behind: Option<usize>,
+    /// Paths in the folder with changes not committed yet
+    #[serde(default)]
+    pub uncommitted: Option<usize>,
+    /// When the remote was last fetched
+    #[serde(default, with = "crate::util::rfc3339::option")]
+    pub last_fetch: Option<SystemTime>,
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default, with = "crate::util::rfc3339::option")]
+    pub paused_until: Option<SystemTime>,
+    #[serde(default)]
+    pub offline: bool,
+    /// Whether a pull and push, or another operation on the repository such as a
+    /// requested gc, is running right now
+    #[serde(default)]
+    pub syncing: bool,
+    /// Backup branches holding local history that a conflicting pull replaced
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+    /// Deletions holding the push until `ghs confirm`
+    #[serde(default)]
+    pub mass_deletion: Option<MassDeletion>,
+    /// Files kept out of the sync because they look like they hold secrets
+    #[serde(default)]
+    pub secrets: Vec<SecretFinding>,
+}
+
+impl FolderStatus {
+    /// A folder no daemon is reporting on
+    pub fn stopped(name: String, path: PathBuf) -> Self {
+        Self {
+            name,
+            path,
+            state: SyncState::Stopped,
+            last_sync: None,
+            last_error: None,
+            next_retry: None,
+            pending_paths: Vec::new(),
+            queued_commits: 0,
+            ahead: None,
+            behind: None,
+            uncommitted: None,
+            last_fetch: None,
+            paused: false,
+            paused_until: None,
+            offline: false,
+            syncing: false,
+            conflicts: Vec::new(),
+            mass_deletion: None,
+            secrets: Vec::new(),
+        }
+    }
+
+    /// The state implied by the other fields for a running daemon
+    pub fn derive_state(&self) -> SyncState {
+        if self.paused {
+            SyncState::Paused
+        } else if self.mass_deletion.is_some() {
+            SyncState::Held
+        } else if self.syncing {
+            SyncState::Syncing
+        } else if self.offline {
+            SyncState::Offline
+        } else if !self.pending_paths.is_empty() || self.queued_commits > 0 {
+            SyncState::Pending
+        } else {
+            SyncState::Idle
+        }
+    }
+
+    /// Fill in what the repository itself knows: ahead/behind against the
+    /// remote-tracking branch, uncommitted paths, last fetch and conflicts.
+    ///
+    /// Best effort; a repository error leaves the affected fields unknown.
+    pub fn fill_git(&mut self, git: &GitSync) {
+        (self.ahead, self.behind) = match git.ahead_behind() {
+            Ok(Some((ahead, behind))) => (Some(ahead), Some(behind)),
+            _ => (None, None),
+        };
+        self.uncommitted = git.uncommitted_count(&self.path).ok();
+        self.last_fetch = git.last_fetch();
+        self.conflicts = git.backup_branches().unwrap_or_default();
+    }
+
+    /// One line for service managers, e.g. `systemctl status`
+    pub fn summary(&self) -> String {
+        match self.state {
+            SyncState::Paused => "Paused".to_string(),
+            SyncState::Held => match self.mass_deletion {
+                Some(deletion) => format!("Push held, {} files deleted", deletion.deleted),
+                None => "Push held".to_string(),
+            },
+            SyncState::Offline => format!("Offline, {} commits queued", self.queued_commits),
+            SyncState::Syncing => "Syncing".to_string(),
+            SyncState::Pending if !self.pending_paths.is_empty() => {
+                format!("{} pending changes", self.pending_paths.len())
+            }
+            SyncState::Pending => format!("{} commits to push", self.queued_commits),
+            SyncState::Idle => "Up to date".to_string(),
+            SyncState::Stopped => "Stopped".to_string(),
+        }
+    }
+}
+
+/// Everything `ghs status` reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Status {
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub remote: Option<String>,
+    pub branch: Option<String>,
+    pub folders: Vec<FolderStatus>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder() -> FolderStatus {
+        let mut status = FolderStatus::stopped("notes".to_string(), PathBuf::from("/home/me/.github-sync/notes"));
+        status.state = SyncState::Idle;
+        status.ahead = Some(0);
+        status.behind = Some(0);
+        status
+    }
+
+    #[test]
+    fn test_derive_state() {
+        let mut status = folder();
+        assert_eq!(status.derive_state(), SyncState::Idle);
+
+        status.pending_paths.push("a.txt".to_string());
+        assert_eq!(status.derive_state(), SyncState::Pending);
+
+        status.offline = true;
+        assert_eq!(status.derive_state(), SyncState::Offline);
+
+        status.paused = true;
+        assert_eq!(status.derive_state(), SyncState::Paused);
+    }
+
+    #[test]
+    fn test_json_field_names() {
+        let mut status = folder();
+        status.state = SyncState::Offline;
+        status.last_sync = Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_760_870_000));
+        let json = serde_json::to_value(&status).unwrap();
+
+        assert_eq!(json["state"], "offline");
+        assert_eq!(json["name"], "notes");
+        assert_eq!(json["ahead"], 0);
+        assert!(json["pending_paths"].is_array());
+        assert!(json["conflicts"].is_array());
+        assert!(json["secrets"].is_array());
+        assert!(json["next_retry"].is_null());
+        assert_eq!(json["last_sync"], "2025-10-19T10:33:20Z");
+        assert_eq!(SyncState::Offline.as_str(), "offline");
+    }
+}
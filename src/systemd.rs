@@ -0,0 +1,142 @@
+// src/systemd.rs
+//
+// Client side of the sd_notify protocol: when systemd starts us with
+// `Type=notify` it passes a datagram socket in `NOTIFY_SOCKET`, and we send it
+// newline-separated `KEY=value` assignments. Without that variable every call is
+// a no-op, so the daemon behaves the same outside systemd.
+
+use std::time::{Duration, Instant};
+
+/// Tell systemd the daemon finished starting up.
+pub fn ready(status: &str) {
+    notify(&format!("READY=1\nSTATUS={}", status));
+}
+
+/// Free-form status line shown by `systemctl status`.
+pub fn status(status: &str) {
+    notify(&format!("STATUS={}", status));
+}
+
+/// Tell systemd the daemon is shutting down and may take a while to flush.
+pub fn stopping() {
+    notify("STOPPING=1\nSTATUS=Flushing pending changes");
+}
+
+/// Send `state` to the notification socket. Returns whether it was delivered.
+pub fn notify(state: &str) -> bool {
+    match std::env::var_os("NOTIFY_SOCKET") {
+        Some(socket) => imp::send(&socket, state).is_ok(),
+        None => false,
+    }
+}
+
+/// Keep-alive pings for `WatchdogSec=`, sent from the main loop so a hung loop
+/// gets the service restarted.
+pub struct Watchdog {
+    interval: Duration,
+    last_ping: Option<Instant>,
+}
+
+impl Watchdog {
+    /// The watchdog systemd enabled for this process, if any.
+    pub fn from_env() -> Option<Self> {
+        let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+        if let Ok(pid) = std::env::var("WATCHDOG_PID")
+            && pid.parse() != Ok(std::process::id())
+        {
+            return None;
+        }
+        Some(Self::new(Duration::from_micros(usec)))
+    }
+
+    fn new(timeout: Duration) -> Self {
+        // Ping at half the timeout, as sd_watchdog_enabled(3) recommends
+        Self { interval: timeout / 2, last_ping: None }
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        self.last_ping.is_none_or(|last| now.duration_since(last) >= self.interval)
+    }
+
+    pub fn ping_if_due(&mut self) {
+        let now = Instant::now();
+        if self.is_due(now) {
+            notify("WATCHDOG=1");
+            self.last_ping = Some(now);
+        }
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::ffi::OsStr;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    pub fn send(socket: &OsStr, state: &str) -> io::Result<()> {
+        let datagram = UnixDatagram::unbound()?;
+        let path = socket.as_bytes();
+        match path.first() {
+            // Linux abstract namespace socket
+            #[cfg(target_os = "linux")]
+            Some(b'@') => {
+                use std::os::linux::net::SocketAddrExt;
+                use std::os::unix::net::SocketAddr;
+                let addr = SocketAddr::from_abstract_name(&path[1..])?;
+                datagram.send_to_addr(state.as_bytes(), &addr)?;
+            }
+            Some(b'/') => {
+                datagram.send_to(state.as_bytes(), socket)?;
+            }
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "unsupported NOTIFY_SOCKET")),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::ffi::OsStr;
+    use std::io;
+
+    pub fn send(_socket: &OsStr, _state: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "sd_notify is only available on Unix"))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_send_to_path_socket() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("notify.sock");
+        let server = UnixDatagram::bind(&path).unwrap();
+
+        imp::send(path.as_os_str(), "READY=1\nSTATUS=Watching").unwrap();
+
+        let mut buf = [0u8; 64];
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1\nSTATUS=Watching");
+    }
+
+    #[test]
+    fn test_unsupported_socket_address() {
+        assert!(imp::send(std::ffi::OsStr::new("relative.sock"), "READY=1").is_err());
+    }
+
+    #[test]
+    fn test_watchdog_pings_at_half_the_timeout() {
+        let mut watchdog = Watchdog::new(Duration::from_secs(60));
+        let start = Instant::now();
+        assert!(watchdog.is_due(start));
+
+        watchdog.last_ping = Some(start);
+        assert!(!watchdog.is_due(start + Duration::from_secs(29)));
+        assert!(watchdog.is_due(start + Duration::from_secs(30)));
+    }
+}
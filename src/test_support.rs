@@ -0,0 +1,33 @@
+// src/test_support.rs
+//
+// Fixtures shared by the unit tests: a bare repository standing in for GitHub and
+// devices that sync through it.
+
+use std::fs;
+use std::path::Path;
+use git2::Repository;
+use tempfile::{tempdir, TempDir};
+use crate::changes::{Change, ChangeSet};
+use crate::git::GitSync;
+
+/// An empty bare repository to use as the remote
+pub fn bare_remote() -> TempDir {
+    let remote = tempdir().unwrap();
+    Repository::init_bare(remote.path()).unwrap();
+    remote
+}
+
+/// A new device syncing with `remote`
+pub fn device(remote: &Path) -> (TempDir, GitSync) {
+    let dir = tempdir().unwrap();
+    let git = GitSync::new(dir.path(), remote.to_str().unwrap(), "main").unwrap();
+    (dir, git)
+}
+
+/// Write `name` under `root` and commit it
+pub fn commit_file(git: &GitSync, root: &Path, name: &str, content: &str) {
+    fs::write(root.join(name), content).unwrap();
+    let mut changes = ChangeSet::default();
+    changes.record(Change::Modified(git.workdir().unwrap().join(name)));
+    git.commit_changes(&changes).unwrap();
+}
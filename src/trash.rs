@@ -0,0 +1,233 @@
+// src/trash.rs
+//
+// Files that a pull deletes because another device removed them are copied here
+// first. The trash lives in the sync root but is excluded from git, so it never
+// syncs: every device keeps its own.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, Local};
+use serde::{Serialize, Deserialize};
+use crate::config::TrashSettings;
+use crate::error::{Result, GitHubSyncError};
+
+/// Directory in the sync root that holds the trash
+pub const TRASH_DIR: &str = ".ghs-trash";
+const INDEX_FILE: &str = "index.json";
+const FILES_DIR: &str = "files";
+
+/// One file in the trash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    /// Where the file was, relative to the sync root
+    pub path: PathBuf,
+    #[serde(with = "crate::util::rfc3339")]
+    pub deleted_at: SystemTime,
+    pub size: u64,
+    /// The pulled commit that deleted it
+    pub commit: Option<String>,
+}
+
+pub struct Trash {
+    dir: PathBuf,
+}
+
+impl Trash {
+    pub fn new(sync_root: &Path) -> Self {
+        Self { dir: sync_root.join(TRASH_DIR) }
+    }
+
+    /// Everything in the trash, newest first
+    pub fn entries(&self) -> Result<Vec<TrashEntry>> {
+        let content = match fs::read_to_string(self.dir.join(INDEX_FILE)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut entries: Vec<TrashEntry> = serde_json::from_str(&content)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Invalid trash index: {}", e)))?;
+        entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then_with(|| b.id.cmp(&a.id)));
+        Ok(entries)
+    }
+
+    /// Copy `files`, pairs of the path relative to the sync root and the file on
+    /// disk, into the trash. Missing files are skipped.
+    pub fn add(&self, files: &[(PathBuf, PathBuf)], commit: Option<&str>) -> Result<Vec<TrashEntry>> {
+        let mut entries = self.entries()?;
+        let now = SystemTime::now();
+        let stamp = DateTime::<Local>::from(now).format("%Y%m%d%H%M%S").to_string();
+        fs::create_dir_all(self.dir.join(FILES_DIR))?;
+
+        let mut added = Vec::new();
+        for (relative, source) in files {
+            let Ok(meta) = fs::symlink_metadata(source) else {
+                continue;
+            };
+            if !meta.is_file() {
+                continue;
+            }
+
+            let id = (1..)
+                .map(|n| format!("{}-{}", stamp, n))
+                .find(|id| !entries.iter().any(|e| e.id == *id))
+                .expect("ids are unbounded");
+            fs::copy(source, self.dir.join(FILES_DIR).join(&id))?;
+
+            let entry = TrashEntry {
+                id,
+                path: relative.clone(),
+                deleted_at: now,
+                size: meta.len(),
+                commit: commit.map(str::to_string),
+            };
+            entries.push(entry.clone());
+            added.push(entry);
+        }
+
+        if !added.is_empty() {
+            self.save(&entries)?;
+        }
+        Ok(added)
+    }
+
+    /// The entry with this id, or the newest one for this path
+    pub fn find(&self, id_or_path: &str) -> Result<Option<TrashEntry>> {
+        Ok(self.entries()?
+            .into_iter()
+            .find(|e| e.id == id_or_path || e.path == Path::new(id_or_path)))
+    }
+
+    /// Move an entry out of the trash to `dest`
+    pub fn restore(&self, entry: &TrashEntry, dest: &Path) -> Result<()> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(self.dir.join(FILES_DIR).join(&entry.id), dest)?;
+        self.remove(|e| e.id == entry.id).map(|_| ())
+    }
+
+    /// Delete entries older than `older_than`, or all of them; returns how many
+    pub fn empty(&self, older_than: Option<Duration>) -> Result<usize> {
+        let cutoff = older_than.map(|age| SystemTime::now() - age);
+        self.remove(|e| cutoff.is_none_or(|cutoff| e.deleted_at < cutoff))
+    }
+
+    /// Apply the retention limits: drop entries past the maximum age, then the
+    /// oldest ones until the trash fits the size limit. Returns how many went.
+    pub fn prune(&self, settings: &TrashSettings) -> Result<usize> {
+        let entries = self.entries()?;
+        let max_age = Duration::from_secs(settings.max_age_days * 24 * 60 * 60);
+        let cutoff = (!max_age.is_zero()).then(|| SystemTime::now() - max_age);
+        let max_size = settings.max_size_mb.saturating_mul(1024 * 1024);
+
+        // Newest first, so everything after the size limit is exceeded goes
+        let mut total = 0u64;
+        let expired: Vec<String> = entries
+            .iter()
+            .filter(|e| {
+                total = total.saturating_add(e.size);
+                cutoff.is_some_and(|cutoff| e.deleted_at < cutoff) || (max_size > 0 && total > max_size)
+            })
+            .map(|e| e.id.clone())
+            .collect();
+        if expired.is_empty() {
+            return Ok(0);
+        }
+        self.remove(|e| expired.contains(&e.id))
+    }
+
+    fn remove(&self, matches: impl Fn(&TrashEntry) -> bool) -> Result<usize> {
+        let (removed, kept): (Vec<_>, Vec<_>) = self.entries()?.into_iter().partition(|e| matches(e));
+        for entry in &removed {
+            match fs::remove_file(self.dir.join(FILES_DIR).join(&entry.id)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        if !removed.is_empty() {
+            self.save(&kept)?;
+        }
+        Ok(removed.len())
+    }
+
+    /// Replace the index in one rename, so a reader never sees half of it
+    fn save(&self, entries: &[TrashEntry]) -> Result<()> {
+        let content = serde_json::to_string_pretty(entries)
+            .map_err(|e| GitHubSyncError::UnknownError(format!("Failed to serialize trash index: {}", e)))?;
+        let tmp = self.dir.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&tmp, content)?;
+        fs::rename(tmp, self.dir.join(INDEX_FILE))?;
+        Ok(())
+    }
+}
+
+pub fn is_trash_path(path: &Path) -> bool {
+    path.components().any(|c| c.as_os_str() == TRASH_DIR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn trash_with(root: &Path, files: &[(&str, &str)]) -> Trash {
+        let trash = Trash::new(root);
+        let files: Vec<(PathBuf, PathBuf)> = files
+            .iter()
+            .map(|(name, content)| {
+                fs::write(root.join(name), content).unwrap();
+                (PathBuf::from(name), root.join(name))
+            })
+            .collect();
+        trash.add(&files, Some("0123abcd")).unwrap();
+        trash
+    }
+
+    #[test]
+    fn test_add_and_restore() {
+        let root = tempdir().unwrap();
+        let trash = trash_with(root.path(), &[("a.txt", "a"), ("b.txt", "bb")]);
+        fs::remove_file(root.path().join("a.txt")).unwrap();
+
+        let entries = trash.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].commit.as_deref(), Some("0123abcd"));
+
+        let entry = trash.find("a.txt").unwrap().unwrap();
+        assert_eq!(entry.size, 1);
+        trash.restore(&entry, &root.path().join("a.txt")).unwrap();
+        assert_eq!(fs::read_to_string(root.path().join("a.txt")).unwrap(), "a");
+        assert!(trash.find("a.txt").unwrap().is_none());
+        assert_eq!(trash.entries().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_prune_by_size_keeps_newest() {
+        let root = tempdir().unwrap();
+        let big = "y".repeat(1024 * 1024);
+        let trash = trash_with(root.path(), &[("old.bin", "x"), ("new.bin", &big)]);
+
+        // Make the first entry clearly older
+        let mut entries = trash.entries().unwrap();
+        entries.iter_mut().find(|e| e.path == Path::new("old.bin")).unwrap().deleted_at -= Duration::from_secs(60);
+        trash.save(&entries).unwrap();
+
+        let removed = trash.prune(&TrashSettings { max_age_days: 0, max_size_mb: 1 }).unwrap();
+        assert_eq!(removed, 1);
+        let left = trash.entries().unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].path, PathBuf::from("new.bin"));
+    }
+
+    #[test]
+    fn test_empty_older_than() {
+        let root = tempdir().unwrap();
+        let trash = trash_with(root.path(), &[("a.txt", "a")]);
+        assert_eq!(trash.empty(Some(Duration::from_secs(3600))).unwrap(), 0);
+        assert_eq!(trash.empty(None).unwrap(), 1);
+        assert!(trash.entries().unwrap().is_empty());
+        assert_eq!(fs::read_dir(root.path().join(TRASH_DIR).join(FILES_DIR)).unwrap().count(), 0);
+    }
+}
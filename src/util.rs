@@ -0,0 +1,164 @@
+// src/util.rs
+//
+// Small helpers shared by the daemon and the commands.
+
+use std::time::Duration;
+
+/// Parse durations like `90s`, `30m`, `2h`, `1d` or combinations such as `1h30m`.
+pub fn parse_duration(input: &str) -> std::result::Result<Duration, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("duration is empty".to_string());
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(format!("unknown unit '{}' in '{}' (use s, m, h or d)", c, input)),
+        };
+        let value: u64 = number.parse()
+            .map_err(|_| format!("missing number before '{}' in '{}'", c, input))?;
+        total = total.saturating_add(value.saturating_mul(unit));
+        number.clear();
+    }
+
+    if !number.is_empty() {
+        return Err(format!("missing unit after '{}' in '{}' (use s, m, h or d)", number, input));
+    }
+    if total == 0 {
+        return Err("duration must be greater than zero".to_string());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// Short human form of a duration, e.g. `1h 59m` or `45s`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes, seconds) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60, secs % 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{}s", seconds),
+        (0, 0, _) => format!("{}m {}s", minutes, seconds),
+        (0, _, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+/// Serde helpers that write a `SystemTime` as an RFC 3339 string such as
+/// `2025-10-19T10:33:20Z`. Reading also accepts the `{secs_since_epoch,
+/// nanos_since_epoch}` form earlier versions wrote to the status file and trash index.
+pub mod rfc3339 {
+    use std::time::{Duration, SystemTime};
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Text(String),
+        Epoch { secs_since_epoch: u64, nanos_since_epoch: u32 },
+    }
+
+    pub fn format(time: SystemTime) -> String {
+        DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    fn parse(repr: Repr) -> Result<SystemTime, String> {
+        match repr {
+            Repr::Text(text) => DateTime::parse_from_rfc3339(&text)
+                .map(SystemTime::from)
+                .map_err(|e| format!("invalid timestamp '{}': {}", text, e)),
+            Repr::Epoch { secs_since_epoch, nanos_since_epoch } => {
+                Ok(SystemTime::UNIX_EPOCH + Duration::new(secs_since_epoch, nanos_since_epoch))
+            }
+        }
+    }
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format(*time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        parse(Repr::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+
+    /// The same for an optional time, written as `null` when absent
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(time: &Option<SystemTime>, serializer: S) -> Result<S::Ok, S::Error> {
+            match time {
+                Some(time) => serializer.serialize_some(&format(*time)),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<SystemTime>, D::Error> {
+            Option::<Repr>::deserialize(deserializer)?
+                .map(parse)
+                .transpose()
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1_800)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7_200)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5_400)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86_400)));
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("2").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("2w").is_err());
+        assert!(parse_duration("0m").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_duration(Duration::from_secs(7_140)), "1h 59m");
+        assert_eq!(format_duration(Duration::from_secs(90_000)), "1d 1h");
+    }
+
+    #[test]
+    fn test_rfc3339_times() {
+        #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+        struct Times {
+            #[serde(with = "rfc3339")]
+            at: std::time::SystemTime,
+            #[serde(default, with = "rfc3339::option")]
+            until: Option<std::time::SystemTime>,
+        }
+
+        let at = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_760_870_000);
+        let times = Times { at, until: None };
+        let json = serde_json::to_string(&times).unwrap();
+        assert_eq!(json, r#"{"at":"2025-10-19T10:33:20Z","until":null}"#);
+        assert_eq!(serde_json::from_str::<Times>(&json).unwrap(), times);
+
+        // Written by earlier versions
+        let old = r#"{"at":{"secs_since_epoch":1760870000,"nanos_since_epoch":0},"until":{"secs_since_epoch":1760870000,"nanos_since_epoch":0}}"#;
+        assert_eq!(serde_json::from_str::<Times>(old).unwrap(), Times { at, until: Some(at) });
+        assert_eq!(serde_json::from_str::<Times>(r#"{"at":"2025-10-19T12:33:20+02:00"}"#).unwrap(), times);
+        assert!(serde_json::from_str::<Times>(r#"{"at":"yesterday"}"#).is_err());
+    }
+}
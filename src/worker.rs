@@ -0,0 +1,155 @@
+// src/worker.rs
+//
+// A folder's repository is owned by its sync worker: a dedicated thread that runs
+// git operations one at a time, in the order they were asked for. libgit2 calls
+// block, the network ones for as long as the remote takes, so they stay off the
+// async runtime; the watcher loop, control requests and timers queue them here and
+// await the answer.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use std::time::Instant;
+use git2::Oid;
+use tokio::sync::oneshot;
+use crate::changes::ChangeSet;
+use crate::echo::EchoFilter;
+use crate::error::{Result, GitHubSyncError};
+use crate::git::GitSync;
+use crate::logger;
+use crate::secrets::SecretFinding;
+use crate::status::FolderStatus;
+
+type Job = Box<dyn FnOnce(&mut GitSync) + Send>;
+
+/// Handle to a folder's sync worker. Clones share the worker, which exits once the
+/// last handle is dropped and the jobs already queued are done.
+#[derive(Clone)]
+pub struct SyncWorker {
+    jobs: Sender<Job>,
+    workdir: Option<PathBuf>,
+    echo: Arc<EchoFilter>,
+    /// The repository's secret findings as of the last finished job
+    findings: Arc<Mutex<Vec<SecretFinding>>>,
+}
+
+impl SyncWorker {
+    /// Move `git` onto a new worker thread for `folder`
+    pub fn spawn(folder: &str, git: GitSync) -> Result<Self> {
+        let workdir = git.workdir().map(Path::to_path_buf);
+        let echo = git.echo_filter();
+        let findings = Arc::new(Mutex::new(git.secret_findings()));
+        let (jobs, queue) = channel::<Job>();
+
+        thread::Builder::new()
+            .name(format!("ghs-sync-{}", folder))
+            .spawn(move || {
+                let mut git = git;
+                for job in queue {
+                    // The caller sees its reply dropped; later jobs still run
+                    if panic::catch_unwind(AssertUnwindSafe(|| job(&mut git))).is_err() {
+                        logger::error("A git operation panicked");
+                    }
+                }
+            })?;
+
+        Ok(Self { jobs, workdir, echo, findings })
+    }
+
+    /// Run `f` on the worker after every job queued before it
+    pub async fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut GitSync) -> Result<T> + Send + 'static,
+    {
+        let (reply, answer) = oneshot::channel();
+        let findings = self.findings.clone();
+        self.jobs
+            .send(Box::new(move |git| {
+                let result = f(git);
+                // Published before the reply, so the caller sees what its job found
+                *findings.lock().unwrap_or_else(|e| e.into_inner()) = git.secret_findings();
+                let _ = reply.send(result);
+            }))
+            .map_err(|_| stopped())?;
+        answer.await.map_err(|_| stopped())?
+    }
+
+    /// Commit exactly the paths in `changes`
+    pub async fn commit(&self, changes: ChangeSet) -> Result<Option<Oid>> {
+        self.call(move |git| git.commit_changes(&changes)).await
+    }
+
+    /// Pull and push, giving up on the network at `deadline`
+    pub async fn sync(&self, deadline: Option<Instant>) -> Result<()> {
+        self.call(move |git| {
+            git.set_deadline(deadline);
+            let result = git.sync();
+            git.set_deadline(None);
+            result
+        })
+        .await
+    }
+
+    /// Add the repository details to `status`
+    pub async fn status(&self, mut status: FolderStatus) -> Result<FolderStatus> {
+        self.call(move |git| {
+            status.fill_git(git);
+            Ok(status)
+        })
+        .await
+    }
+
+    /// The sync root; fixed for the worker's lifetime, so answered without a job
+    pub fn workdir(&self) -> Option<&Path> {
+        self.workdir.as_deref()
+    }
+
+    pub fn echo_filter(&self) -> Arc<EchoFilter> {
+        self.echo.clone()
+    }
+
+    /// Files kept out of the sync as of the last finished job; answered without a
+    /// job so status reports never wait behind a sync
+    pub fn secret_findings(&self) -> Vec<SecretFinding> {
+        self.findings.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+fn stopped() -> GitHubSyncError {
+    GitHubSyncError::UnknownError("The sync worker has stopped".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::changes::Change;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_jobs_run_in_order_off_the_runtime() {
+        let temp = tempdir().unwrap();
+        let git = GitSync::new(temp.path(), "git@github.com:test/repo.git", "main").unwrap();
+        let worker = SyncWorker::spawn("test", git).unwrap();
+        let workdir = worker.workdir().unwrap().to_path_buf();
+
+        let runtime_thread = thread::current().id();
+        let worker_thread = worker.call(|_| Ok(thread::current().id())).await.unwrap();
+        assert_ne!(worker_thread, runtime_thread);
+
+        // Queued together, the second job only runs once the commit is done
+        fs::write(workdir.join("a.txt"), "a").unwrap();
+        let mut changes = ChangeSet::default();
+        changes.record(Change::Created(workdir.join("a.txt")));
+        let (commit, dirty) = tokio::join!(worker.commit(changes), worker.call(|git| git.dirty_paths()));
+        assert!(commit.unwrap().is_some());
+        assert!(dirty.unwrap().is_empty());
+
+        let panicked = worker.call(|_| -> Result<()> { panic!("boom") }).await;
+        assert!(panicked.is_err());
+        assert!(worker.call(|git| git.dirty_paths()).await.is_ok());
+    }
+}